@@ -0,0 +1,32 @@
+// This is the aim module, it tracks where the player is aiming when manual aiming is turned on
+
+use crate::camera::CursorPosition;
+use crate::player::Player;
+use crate::settings::Settings;
+use bevy::prelude::*;
+
+// The direction the player is aiming in, None when there is no aim input
+#[derive(Resource, Default)]
+pub struct AimDirection(pub Option<Vec2>);
+
+// The right stick wins over the mouse, so a gamepad player can leave the cursor anywhere on the screen
+pub fn update_aim_direction(
+    settings: Res<Settings>,
+    cursor: Res<CursorPosition>,
+    mut aim: ResMut<AimDirection>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let stick = gamepads.iter().find_map(|gamepad| {
+        let x = axes.get(GamepadAxis::new(gamepad, GamepadAxisType::RightStickX))?;
+        let y = axes.get(GamepadAxis::new(gamepad, GamepadAxisType::RightStickY))?;
+        let stick = Vec2::new(x, y);
+        (stick.length() > settings.stick_deadzone).then_some(stick)
+    });
+    let mouse = match (cursor.0, player_query.get_single()) {
+        (Some(cursor), Ok(player_transform)) => Some(cursor - player_transform.translation.truncate()),
+        _ => None,
+    };
+    aim.0 = stick.or(mouse).and_then(|direction| direction.try_normalize());
+}
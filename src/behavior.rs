@@ -0,0 +1,244 @@
+// This is the behavior module, every enemy has an EnemyBehavior deciding how it moves
+// Each behavior has its own system, so a new behavior only needs a new variant and a new system
+
+use crate::enemy::Enemy;
+use crate::pathfinding::FlowField;
+use crate::player::Player;
+use crate::status::StatusEffects;
+use bevy::prelude::*;
+use rand::Rng;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChargeState {
+    // Walking towards the player until it is close enough to charge
+    Approach,
+    // Standing still, getting ready to charge
+    Windup,
+    // Running in a straight line, not turning towards the player
+    Dash,
+}
+
+#[derive(Component, Clone, Debug)]
+pub enum EnemyBehavior {
+    // Walk straight at the player
+    Chase,
+    // Walk up to the player, stop for a moment, then dash in a straight line
+    Charge {
+        state: ChargeState,
+        timer: Timer,
+        direction: Vec2,
+    },
+    // Circle around the player at a distance
+    Orbit { radius: f32, clockwise: bool },
+    // Flutter towards the player in an erratic way, like a swarm of bats
+    Swarm { wander: Vec2, timer: Timer },
+    // Walk up to the player so it's seen, then run away and escape with its loot once the timer runs out
+    Flee { escape: Timer, spotted: bool },
+}
+
+// How far a charger gets before it winds up, and how long the windup and dash take
+pub const CHARGE_DISTANCE: f32 = 250.0;
+pub const CHARGE_WINDUP: f32 = 0.8;
+pub const CHARGE_DURATION: f32 = 0.7;
+pub const CHARGE_SPEED_MULTIPLIER: f32 = 4.0;
+// How close a fleeing enemy comes before it turns around, well inside the screen
+pub const FLEE_DISTANCE: f32 = 200.0;
+
+impl EnemyBehavior {
+    pub fn charge() -> Self {
+        EnemyBehavior::Charge {
+            state: ChargeState::Approach,
+            timer: Timer::from_seconds(CHARGE_WINDUP, TimerMode::Once),
+            direction: Vec2::ZERO,
+        }
+    }
+
+    pub fn orbit() -> Self {
+        EnemyBehavior::Orbit {
+            radius: 200.0,
+            clockwise: rand::random(),
+        }
+    }
+
+    pub fn swarm() -> Self {
+        EnemyBehavior::Swarm {
+            wander: Vec2::ZERO,
+            timer: Timer::from_seconds(0.3, TimerMode::Repeating),
+        }
+    }
+
+    pub fn flee() -> Self {
+        EnemyBehavior::Flee {
+            escape: Timer::from_seconds(15.0, TimerMode::Once),
+            spotted: false,
+        }
+    }
+}
+
+// Move a transform in a direction, the direction does not need to be normalized
+fn step(transform: &mut Transform, direction: Vec2, speed: f32, delta: f32) {
+    let movement = direction.normalize_or_zero() * speed * delta;
+    transform.translation.x += movement.x;
+    transform.translation.y += movement.y;
+}
+
+// The way to the player, following the flow field around obstacles, or straight at the player outside of it
+fn towards_player(flow_field: &FlowField, position: Vec2, player_position: Vec2) -> Vec2 {
+    flow_field
+        .direction(position)
+        .unwrap_or((player_position - position).normalize_or_zero())
+}
+
+// Frozen and slowed enemies move slower or not at all
+fn move_speed(enemy: &Enemy, statuses: &StatusEffects) -> f32 {
+    enemy.move_speed * statuses.movement_multiplier()
+}
+
+// Function to make chasing enemies move towards the player, based on move speed, include delta time for smooth movement
+pub fn chase_player(
+    time: Res<Time>,
+    flow_field: Res<FlowField>,
+    player_query: Query<&Transform, (With<Player>, Without<Enemy>)>,
+    mut enemy_query: Query<(&mut Transform, &Enemy, &StatusEffects, &EnemyBehavior), Without<Player>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let player_position = player_transform.translation.truncate();
+    for (mut transform, enemy, statuses, behavior) in enemy_query.iter_mut() {
+        if !matches!(behavior, EnemyBehavior::Chase) {
+            continue;
+        }
+        let direction = towards_player(&flow_field, transform.translation.truncate(), player_position);
+        step(&mut transform, direction, move_speed(enemy, statuses), time.delta_seconds());
+    }
+}
+
+pub fn charge_player(
+    time: Res<Time>,
+    flow_field: Res<FlowField>,
+    player_query: Query<&Transform, (With<Player>, Without<Enemy>)>,
+    mut enemy_query: Query<(&mut Transform, &Enemy, &StatusEffects, &mut EnemyBehavior), Without<Player>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let player_position = player_transform.translation.truncate();
+    for (mut transform, enemy, statuses, mut behavior) in enemy_query.iter_mut() {
+        let EnemyBehavior::Charge { state, timer, direction } = behavior.as_mut() else {
+            continue;
+        };
+        let speed = move_speed(enemy, statuses);
+        // Frozen chargers don't get to continue their windup or dash either
+        if speed <= 0.0 {
+            continue;
+        }
+        let to_player = player_position - transform.translation.truncate();
+        match state {
+            ChargeState::Approach => {
+                if to_player.length() < CHARGE_DISTANCE {
+                    *state = ChargeState::Windup;
+                    *timer = Timer::from_seconds(CHARGE_WINDUP, TimerMode::Once);
+                } else {
+                    let direction = towards_player(&flow_field, transform.translation.truncate(), player_position);
+                    step(&mut transform, direction, speed, time.delta_seconds());
+                }
+            }
+            ChargeState::Windup => {
+                if timer.tick(time.delta()).finished() {
+                    // The direction is locked in when the dash starts
+                    *state = ChargeState::Dash;
+                    *direction = to_player.normalize_or_zero();
+                    *timer = Timer::from_seconds(CHARGE_DURATION, TimerMode::Once);
+                }
+            }
+            ChargeState::Dash => {
+                step(&mut transform, *direction, speed * CHARGE_SPEED_MULTIPLIER, time.delta_seconds());
+                if timer.tick(time.delta()).finished() {
+                    *state = ChargeState::Approach;
+                }
+            }
+        }
+    }
+}
+
+pub fn orbit_player(
+    time: Res<Time>,
+    player_query: Query<&Transform, (With<Player>, Without<Enemy>)>,
+    mut enemy_query: Query<(&mut Transform, &Enemy, &StatusEffects, &EnemyBehavior), Without<Player>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let player_position = player_transform.translation.truncate();
+    for (mut transform, enemy, statuses, behavior) in enemy_query.iter_mut() {
+        let EnemyBehavior::Orbit { radius, clockwise } = *behavior else {
+            continue;
+        };
+        let from_player = transform.translation.truncate() - player_position;
+        let outwards = from_player.normalize_or_zero();
+        let sideways = if clockwise { -outwards.perp() } else { outwards.perp() };
+        // Move around the player while correcting towards the orbit radius
+        let correction = (radius - from_player.length()) / radius;
+        let direction = sideways + outwards * correction.clamp(-1.0, 1.0);
+        step(&mut transform, direction, move_speed(enemy, statuses), time.delta_seconds());
+    }
+}
+
+pub fn swarm_player(
+    time: Res<Time>,
+    flow_field: Res<FlowField>,
+    player_query: Query<&Transform, (With<Player>, Without<Enemy>)>,
+    mut enemy_query: Query<(&mut Transform, &Enemy, &StatusEffects, &mut EnemyBehavior), Without<Player>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let player_position = player_transform.translation.truncate();
+    let mut rng = rand::thread_rng();
+    for (mut transform, enemy, statuses, mut behavior) in enemy_query.iter_mut() {
+        let EnemyBehavior::Swarm { wander, timer } = behavior.as_mut() else {
+            continue;
+        };
+        // Pick a new random wobble every now and then
+        if timer.tick(time.delta()).just_finished() {
+            *wander = Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU));
+        }
+        let to_player = towards_player(&flow_field, transform.translation.truncate(), player_position);
+        step(&mut transform, to_player + *wander * 0.8, move_speed(enemy, statuses), time.delta_seconds());
+    }
+}
+
+pub fn flee_player(
+    time: Res<Time>,
+    mut commands: Commands,
+    flow_field: Res<FlowField>,
+    player_query: Query<&Transform, (With<Player>, Without<Enemy>)>,
+    mut enemy_query: Query<(Entity, &mut Transform, &Enemy, &StatusEffects, &mut EnemyBehavior), Without<Player>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let player_position = player_transform.translation.truncate();
+    for (entity, mut transform, enemy, statuses, mut behavior) in enemy_query.iter_mut() {
+        let EnemyBehavior::Flee { escape, spotted } = behavior.as_mut() else {
+            continue;
+        };
+        let position = transform.translation.truncate();
+        // Enemies spawn off screen, so they come into view first and only start running once the player is close
+        if !*spotted {
+            if position.distance(player_position) > FLEE_DISTANCE {
+                let to_player = towards_player(&flow_field, position, player_position);
+                step(&mut transform, to_player, move_speed(enemy, statuses), time.delta_seconds());
+                continue;
+            }
+            *spotted = true;
+        }
+        // Escaped enemies are gone for good, without dropping anything
+        if escape.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        step(&mut transform, position - player_position, move_speed(enemy, statuses), time.delta_seconds());
+    }
+}
@@ -1,7 +1,8 @@
 // This is the camera module, the camera follows the player
-use bevy::{ecs::query::WorldQuery, prelude::*};
+use bevy::{ecs::query::WorldQuery, prelude::*, window::PrimaryWindow};
 use crate::player;
 use crate::enemy;
+use crate::obstacle::MapBounds;
 
 #[derive(Component, WorldQuery)]
 pub struct Camera;
@@ -17,14 +18,42 @@ pub fn create_camera(commands: &mut Commands) {
 }
 
 // Function to make the camera center on the player, there are also enemy components in the game, so we need to exclude them
+// In bounded maps the camera stops at the edges instead, so it never shows what's outside of the map
 pub fn follow_player(
+    bounds: Res<MapBounds>,
     player_query: Query<(&player::Player, &Transform), (With<player::Player>, Without<enemy::Enemy>, Without<Camera>)>,
-    mut camera_query: Query<(&mut Transform, &Camera)>,
+    mut camera_query: Query<(&mut Transform, &OrthographicProjection, &Camera)>,
 ) {
     for (_, player_transform) in player_query.iter() {
-        for (mut transform, _) in camera_query.iter_mut() {
-            transform.translation.x = player_transform.translation.x;
-            transform.translation.y = player_transform.translation.y;
+        for (mut transform, projection, _) in camera_query.iter_mut() {
+            let position = bounds.clamp(player_transform.translation.truncate(), projection.area.size());
+            transform.translation.x = position.x;
+            transform.translation.y = position.y;
         }
     }
 }
+
+// Where the mouse cursor is in world coordinates, None when the cursor is outside of the window
+#[derive(Resource, Default)]
+pub struct CursorPosition(pub Option<Vec2>);
+
+// Convert the cursor position on the window into world coordinates, using the camera that follows the player
+pub fn update_cursor_position(
+    mut cursor: ResMut<CursorPosition>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&bevy::render::camera::Camera, &GlobalTransform), With<Camera>>,
+) {
+    let (Ok(window), Ok((camera, camera_transform))) = (window_query.get_single(), camera_query.get_single()) else {
+        cursor.0 = None;
+        return;
+    };
+    cursor.0 = window
+        .cursor_position()
+        .and_then(|position| camera.viewport_to_world_2d(camera_transform, position));
+}
+
+// The part of the world the camera can currently see
+pub fn visible_area(projection: &OrthographicProjection, camera_transform: &Transform) -> Rect {
+    let center = camera_transform.translation.truncate();
+    Rect::from_corners(center + projection.area.min, center + projection.area.max)
+}
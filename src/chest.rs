@@ -0,0 +1,307 @@
+// This is the chest module, opening a treasure chest pauses the game and rolls rewards for the player
+// The rewards depend on the player's luck and on which weapons they own
+
+use crate::game_state::GameState;
+use crate::player::Player;
+use crate::projectile::ProjectileTimer;
+use crate::weapon::{self, NextSlot, Weapon, MAX_WEAPONS, MAX_WEAPON_LEVEL};
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChestReward {
+    LevelUp {
+        weapon: Entity,
+        name: String,
+        level: u32,
+    },
+    Evolution {
+        weapon: Entity,
+        name: String,
+    },
+    // A weapon from the weapon pool the player didn't have yet
+    NewWeapon {
+        name: String,
+    },
+    Gold(u32),
+}
+
+// Chance for a reward to be a new weapon while the player has room for one
+pub const NEW_WEAPON_CHANCE: f64 = 0.3;
+
+impl ChestReward {
+    pub fn describe(&self) -> String {
+        match self {
+            ChestReward::LevelUp { name, level, .. } => format!("{} reached level {}", name, level),
+            ChestReward::Evolution { name, .. } => format!("{} evolved!", name),
+            ChestReward::NewWeapon { name } => format!("New weapon: {}", name),
+            ChestReward::Gold(amount) => format!("{} gold", amount),
+        }
+    }
+}
+
+// What the reward roll needs to know about a weapon the player owns
+#[derive(Clone, Debug)]
+pub struct OwnedWeapon {
+    pub entity: Entity,
+    pub name: String,
+    pub level: u32,
+    pub evolved: bool,
+}
+
+// A chest holds 1, 3 or 5 rewards, luck makes the bigger chests more likely
+pub fn reward_count(luck: f32, rng: &mut impl Rng) -> usize {
+    let weights = [(1, 70.0), (3, 25.0 * luck), (5, 5.0 * luck)];
+    let total: f32 = weights.iter().map(|(_, weight)| weight).sum();
+    let mut pick = rng.gen_range(0.0..total);
+    for (count, weight) in weights {
+        if pick < weight {
+            return count;
+        }
+        pick -= weight;
+    }
+    1
+}
+
+// Roll the rewards of a chest, evolutions come first, then new weapons or level ups for random weapons, and gold once everything is maxed out
+// new_weapons are the names of the weapons the player can still find
+pub fn roll_rewards(
+    luck: f32,
+    weapons: &[OwnedWeapon],
+    new_weapons: &[String],
+    rng: &mut impl Rng,
+) -> Vec<ChestReward> {
+    // Keep track of the rolled levels so a chest never levels a weapon past the max
+    let mut weapons = weapons.to_vec();
+    // And of the weapons found, so a chest never gives the same weapon twice or more than the player can carry
+    let mut new_weapons = new_weapons.to_vec();
+    let mut carried = weapons.len();
+    (0..reward_count(luck, rng))
+        .map(|_| {
+            if let Some(weapon) = weapons
+                .iter_mut()
+                .find(|weapon| weapon.level >= MAX_WEAPON_LEVEL && !weapon.evolved)
+            {
+                weapon.evolved = true;
+                return ChestReward::Evolution {
+                    weapon: weapon.entity,
+                    name: weapon.name.clone(),
+                };
+            }
+            let upgradable: Vec<usize> = (0..weapons.len())
+                .filter(|&index| weapons[index].level < MAX_WEAPON_LEVEL)
+                .collect();
+            if carried < MAX_WEAPONS
+                && !new_weapons.is_empty()
+                && (upgradable.is_empty() || rng.gen_bool(NEW_WEAPON_CHANCE))
+            {
+                carried += 1;
+                let name = new_weapons.remove(rng.gen_range(0..new_weapons.len()));
+                return ChestReward::NewWeapon { name };
+            }
+            match upgradable.choose(rng) {
+                Some(&index) => {
+                    let weapon = &mut weapons[index];
+                    weapon.level += 1;
+                    ChestReward::LevelUp {
+                        weapon: weapon.entity,
+                        name: weapon.name.clone(),
+                        level: weapon.level,
+                    }
+                }
+                None => ChestReward::Gold(rng.gen_range(10..=50)),
+            }
+        })
+        .collect()
+}
+
+// The chest that is currently being opened, rewards are revealed one at a time
+#[derive(Resource)]
+pub struct OpenChest {
+    pub rewards: Vec<ChestReward>,
+    pub revealed: usize,
+    pub reveal_timer: Timer,
+}
+
+#[derive(Component)]
+pub struct ChestScreen;
+
+#[derive(Component)]
+pub struct ChestTitle;
+
+#[derive(Component)]
+pub struct ChestRewardList;
+
+#[derive(Component)]
+pub struct ChestHint;
+
+// Roll and apply the rewards when the chest is opened, then show the chest screen
+pub fn open_chest(
+    mut commands: Commands,
+    mut next_slot: ResMut<NextSlot>,
+    mut player_query: Query<&mut Player>,
+    mut weapon_query: Query<(Entity, &mut Weapon)>,
+) {
+    let luck = player_query.get_single().map_or(1.0, |player| player.luck);
+    let owned: Vec<OwnedWeapon> = weapon_query
+        .iter()
+        .map(|(entity, weapon)| OwnedWeapon {
+            entity,
+            name: weapon.name.clone(),
+            level: weapon.level,
+            evolved: weapon.evolved,
+        })
+        .collect();
+    let new_weapons: Vec<String> = weapon::weapon_pool()
+        .into_iter()
+        .map(|(weapon, _)| weapon.name)
+        .filter(|name| !weapon_query.iter().any(|(_, owned)| owned.base_name() == name))
+        .collect();
+    let rewards = roll_rewards(luck, &owned, &new_weapons, &mut rand::thread_rng());
+    for reward in &rewards {
+        match reward {
+            ChestReward::LevelUp { weapon, .. } => {
+                if let Ok((_, mut weapon)) = weapon_query.get_mut(*weapon) {
+                    weapon.level_up();
+                }
+            }
+            ChestReward::Evolution { weapon, .. } => {
+                if let Ok((_, mut weapon)) = weapon_query.get_mut(*weapon) {
+                    weapon.evolve();
+                }
+            }
+            ChestReward::NewWeapon { name } => {
+                let found = weapon::weapon_pool().into_iter().find(|(weapon, _)| &weapon.name == name);
+                if let Some((weapon, interval)) = found {
+                    commands.spawn((
+                        ProjectileTimer(Timer::from_seconds(interval, TimerMode::Repeating)),
+                        Weapon {
+                            slot: next_slot.take(),
+                            ..weapon
+                        },
+                    ));
+                }
+            }
+            ChestReward::Gold(amount) => {
+                for mut player in player_query.iter_mut() {
+                    player.gold += amount;
+                }
+            }
+        }
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(20.0),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+                ..default()
+            },
+            ChestScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "Treasure Chest!",
+                    TextStyle {
+                        font_size: 60.0,
+                        color: Color::GOLD,
+                        ..default()
+                    },
+                ),
+                ChestTitle,
+            ));
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(10.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                ChestRewardList,
+            ));
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 30.0,
+                        ..default()
+                    },
+                ),
+                ChestHint,
+            ));
+        });
+
+    commands.insert_resource(OpenChest {
+        rewards,
+        revealed: 0,
+        reveal_timer: Timer::from_seconds(0.6, TimerMode::Repeating),
+    });
+}
+
+// Pulse the title and reveal the rewards one by one, once they are all shown the player can close the chest
+pub fn animate_chest(
+    mut commands: Commands,
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut open_chest: ResMut<OpenChest>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut title_query: Query<&mut Text, (With<ChestTitle>, Without<ChestHint>)>,
+    mut hint_query: Query<&mut Text, (With<ChestHint>, Without<ChestTitle>)>,
+    list_query: Query<Entity, With<ChestRewardList>>,
+) {
+    for mut title in title_query.iter_mut() {
+        title.sections[0].style.font_size = 60.0 + (time.elapsed_seconds() * 6.0).sin() * 6.0;
+    }
+
+    if open_chest.revealed < open_chest.rewards.len() {
+        if open_chest.reveal_timer.tick(time.delta()).just_finished() {
+            let reward = open_chest.rewards[open_chest.revealed].describe();
+            for list in list_query.iter() {
+                commands.entity(list).with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        reward.clone(),
+                        TextStyle {
+                            font_size: 35.0,
+                            ..default()
+                        },
+                    ));
+                });
+            }
+            open_chest.revealed += 1;
+        }
+        return;
+    }
+
+    for mut hint in hint_query.iter_mut() {
+        hint.sections[0].value = "Press Space to continue".to_string();
+    }
+    let confirm = keyboard_input.any_just_pressed([KeyCode::Space, KeyCode::Return])
+        || gamepad_buttons
+            .get_just_pressed()
+            .any(|button| button.button_type == GamepadButtonType::South);
+    if confirm {
+        next_state.set(GameState::Playing);
+    }
+}
+
+pub fn close_chest(mut commands: Commands, screen_query: Query<Entity, With<ChestScreen>>) {
+    for screen in screen_query.iter() {
+        commands.entity(screen).despawn_recursive();
+    }
+    commands.remove_resource::<OpenChest>();
+}
@@ -0,0 +1,123 @@
+// This is the damage module, anything that hurts an enemy or breaks a prop sends a DamageEvent instead of changing its health directly
+// That way projectiles, hazards and everything else share the same death handling
+
+use crate::enemy::{Enemy, EnemyKind};
+use crate::elite::{Elite, EliteAffix};
+use crate::prop::{Destructible, Prop, PropDestroyedEvent};
+use crate::status::StatusEffects;
+use bevy::prelude::*;
+
+// What dealt the damage, damage numbers are colored by it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DamageKind {
+    #[default]
+    Physical,
+    Holy,
+    Fire,
+    Poison,
+}
+
+impl DamageKind {
+    pub fn color(&self) -> Color {
+        match self {
+            DamageKind::Physical => Color::WHITE,
+            DamageKind::Holy => Color::rgb(0.5, 0.8, 1.0),
+            DamageKind::Fire => Color::rgb(1.0, 0.5, 0.1),
+            DamageKind::Poison => Color::rgb(0.4, 1.0, 0.4),
+        }
+    }
+}
+
+#[derive(Event)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub amount: i32,
+    pub kind: DamageKind,
+    // Critical hits already have their extra damage included in the amount
+    pub critical: bool,
+    // Lethal hits like the bomb pickup kill outright, they go straight through elite shields
+    pub lethal: bool,
+}
+
+// Sent for every hit that took health off an enemy, with the damage it really did after weaken
+#[derive(Event)]
+pub struct DamageDealtEvent {
+    pub target: Entity,
+    pub position: Vec2,
+    pub amount: i32,
+    pub kind: DamageKind,
+    pub critical: bool,
+}
+
+// Sent when an enemy dies, the enemy entity is already despawned so everything needed for drops is copied over
+#[derive(Event)]
+pub struct EnemyDeathEvent {
+    pub position: Vec2,
+    pub kind: EnemyKind,
+    pub experience: i32,
+    // The affix of the enemy if it was an elite
+    pub elite: Option<EliteAffix>,
+}
+
+// Apply every damage event sent this frame, enemies and props that drop to zero health are despawned
+pub fn apply_damage(
+    mut commands: Commands,
+    mut damage_events: EventReader<DamageEvent>,
+    mut death_events: EventWriter<EnemyDeathEvent>,
+    mut dealt_events: EventWriter<DamageDealtEvent>,
+    mut destroyed_events: EventWriter<PropDestroyedEvent>,
+    mut enemy_query: Query<(&Transform, &mut Enemy, Option<&StatusEffects>, Option<&mut Elite>)>,
+    mut prop_query: Query<(&Transform, &mut Destructible, &Prop)>,
+) {
+    for event in damage_events.read() {
+        let Ok((enemy_transform, mut enemy, statuses, mut elite)) = enemy_query.get_mut(event.target) else {
+            // Props don't have statuses or shields, they just break
+            if let Ok((prop_transform, mut destructible, prop)) = prop_query.get_mut(event.target) {
+                if destructible.health <= 0 {
+                    continue;
+                }
+                destructible.health -= event.amount;
+                if destructible.health <= 0 {
+                    commands.entity(event.target).despawn_recursive();
+                    destroyed_events.send(PropDestroyedEvent {
+                        position: prop_transform.translation.truncate(),
+                        kind: prop.kind,
+                    });
+                }
+            }
+            continue;
+        };
+        // The enemy may already have died from an earlier event this frame
+        if enemy.health <= 0 || enemy.kind.unbeatable() {
+            continue;
+        }
+        // Shielded elites ignore hits until their shield is gone
+        if let Some(elite) = elite.as_mut() {
+            if elite.shield > 0 && !event.lethal {
+                elite.shield -= 1;
+                continue;
+            }
+        }
+        // Weakened enemies take more damage
+        let multiplier = statuses.map_or(1.0, |statuses| statuses.damage_taken_multiplier());
+        let amount = (event.amount as f32 * multiplier).round() as i32;
+        enemy.health -= amount;
+        dealt_events.send(DamageDealtEvent {
+            target: event.target,
+            position: enemy_transform.translation.truncate(),
+            amount,
+            kind: event.kind,
+            critical: event.critical,
+        });
+        if enemy.health <= 0 {
+            // Elites have their outline as a child, so take the children with them
+            commands.entity(event.target).despawn_recursive();
+            death_events.send(EnemyDeathEvent {
+                position: enemy_transform.translation.truncate(),
+                kind: enemy.kind,
+                experience: enemy.experience,
+                elite: elite.map(|elite| elite.affix),
+            });
+        }
+    }
+}
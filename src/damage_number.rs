@@ -0,0 +1,142 @@
+// This is the damage number module, every hit on an enemy shows how much damage it did as text floating above it
+// Numbers are colored by the kind of damage, critical hits are bigger, and they drift up and fade away
+// Hits landing on the same enemy in quick succession add up into one number, and only so many numbers are shown at once
+
+use crate::damage::{DamageDealtEvent, DamageKind};
+use bevy::prelude::*;
+
+// The most damage numbers on screen at once, hits beyond that don't get a number
+pub const MAX_DAMAGE_NUMBERS: usize = 60;
+// Seconds a damage number stays on screen
+pub const DAMAGE_NUMBER_LIFETIME: f32 = 0.8;
+// Hits of the same kind on the same enemy within this many seconds of a number appearing add to that number
+pub const MERGE_WINDOW: f32 = 0.25;
+// How fast numbers drift upwards
+const RISE_SPEED: f32 = 40.0;
+// How far above the center of the enemy numbers appear
+const SPAWN_OFFSET: f32 = 20.0;
+
+#[derive(Component)]
+pub struct DamageNumber {
+    // The enemy that was hit, it may already be despawned
+    pub target: Entity,
+    pub kind: DamageKind,
+    pub amount: i32,
+    // Set when any of the merged hits was critical
+    pub critical: bool,
+    // Seconds since the number appeared
+    pub age: f32,
+}
+
+impl DamageNumber {
+    pub fn new(target: Entity, kind: DamageKind, amount: i32, critical: bool) -> Self {
+        DamageNumber {
+            target,
+            kind,
+            amount,
+            critical,
+            age: 0.0,
+        }
+    }
+
+    // Whether a new hit should be added to this number instead of getting its own
+    pub fn absorbs(&self, target: Entity, kind: DamageKind) -> bool {
+        self.target == target && self.kind == kind && self.age < MERGE_WINDOW
+    }
+
+    pub fn add(&mut self, amount: i32, critical: bool) {
+        self.amount += amount;
+        self.critical |= critical;
+    }
+
+    pub fn label(&self) -> String {
+        if self.critical {
+            format!("{}!", self.amount)
+        } else {
+            self.amount.to_string()
+        }
+    }
+
+    pub fn font_size(&self) -> f32 {
+        if self.critical {
+            32.0
+        } else {
+            20.0
+        }
+    }
+
+    // Numbers stay solid for the first half of their life, then fade out
+    pub fn alpha(&self) -> f32 {
+        let progress = self.age / DAMAGE_NUMBER_LIFETIME;
+        ((1.0 - progress) * 2.0).clamp(0.0, 1.0)
+    }
+
+    fn text_style(&self) -> TextStyle {
+        TextStyle {
+            font_size: self.font_size(),
+            color: self.kind.color().with_a(self.alpha()),
+            ..default()
+        }
+    }
+}
+
+// Turn the damage dealt this frame into numbers, adding to numbers that are still fresh where possible
+pub fn spawn_damage_numbers(
+    mut commands: Commands,
+    mut dealt_events: EventReader<DamageDealtEvent>,
+    mut number_query: Query<(&mut DamageNumber, &mut Text)>,
+) {
+    // Numbers spawned this frame aren't in the query yet, so they are collected here first
+    let mut new_numbers: Vec<(DamageNumber, Vec2)> = Vec::new();
+    for event in dealt_events.read() {
+        if let Some((mut number, mut text)) = number_query
+            .iter_mut()
+            .find(|(number, _)| number.absorbs(event.target, event.kind))
+        {
+            number.add(event.amount, event.critical);
+            text.sections[0] = TextSection::new(number.label(), number.text_style());
+            continue;
+        }
+        if let Some((number, _)) = new_numbers
+            .iter_mut()
+            .find(|(number, _)| number.absorbs(event.target, event.kind))
+        {
+            number.add(event.amount, event.critical);
+            continue;
+        }
+        new_numbers.push((
+            DamageNumber::new(event.target, event.kind, event.amount, event.critical),
+            event.position,
+        ));
+    }
+    let room = MAX_DAMAGE_NUMBERS.saturating_sub(number_query.iter().len());
+    for (number, position) in new_numbers.into_iter().take(room) {
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(number.label(), number.text_style()),
+                // Above the player, enemies and projectiles
+                transform: Transform::from_xyz(position.x, position.y + SPAWN_OFFSET, 10.0),
+                ..default()
+            },
+            number,
+        ));
+    }
+}
+
+// Drift every number upwards and fade it out, despawning it once its lifetime is over
+pub fn animate_damage_numbers(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut number_query: Query<(Entity, &mut DamageNumber, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut number, mut transform, mut text) in number_query.iter_mut() {
+        number.age += time.delta_seconds();
+        if number.age >= DAMAGE_NUMBER_LIFETIME {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation.y += RISE_SPEED * time.delta_seconds();
+        let alpha = number.alpha();
+        text.sections[0].style.color.set_a(alpha);
+    }
+}
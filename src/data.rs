@@ -0,0 +1,18 @@
+// This is the data module, game data like loot tables, stages and maps are files in the assets folder
+// Paths are resolved the same way Bevy finds its assets, so the game works no matter which directory it is started from
+
+use bevy::asset::io::file::FileAssetReader;
+use std::fmt::Display;
+use std::path::PathBuf;
+
+// Where a data file is on disk, relative is a path like "assets/data/stages.ron"
+pub fn path(relative: &str) -> PathBuf {
+    FileAssetReader::get_base_path().join(relative)
+}
+
+// Read a data file and parse it, the error says which file failed and why
+pub fn read<T, E: Display>(relative: &str, parse: impl FnOnce(&str) -> Result<T, E>) -> Result<T, String> {
+    let source =
+        std::fs::read_to_string(path(relative)).map_err(|error| format!("Could not read {}: {}", relative, error))?;
+    parse(&source).map_err(|error| format!("Could not parse {}: {}", relative, error))
+}
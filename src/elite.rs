@@ -0,0 +1,130 @@
+// This is the elite module, any spawned enemy has a chance to be promoted to a bigger, tougher elite with a random affix
+// Elites always drop a treasure chest
+
+use crate::damage::EnemyDeathEvent;
+use crate::enemy::{self, Enemy};
+use crate::stage::RunClock;
+use bevy::prelude::*;
+use rand::Rng;
+
+// How much bigger and tougher elites are than normal enemies
+pub const ELITE_SCALE: f32 = 1.5;
+pub const ELITE_HEALTH_MULTIPLIER: i32 = 4;
+// Hits a shielded elite ignores before it starts taking damage
+pub const ELITE_SHIELD_HITS: i32 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EliteAffix {
+    // Moves a lot faster
+    Fast,
+    // Slowly heals back to full health
+    Regenerating,
+    // Splits into two normal enemies of its kind when it dies
+    Splitting,
+    // Ignores the first few hits
+    Shielded,
+}
+
+impl EliteAffix {
+    pub fn random(rng: &mut impl Rng) -> Self {
+        match rng.gen_range(0..4) {
+            0 => EliteAffix::Fast,
+            1 => EliteAffix::Regenerating,
+            2 => EliteAffix::Splitting,
+            _ => EliteAffix::Shielded,
+        }
+    }
+
+    // The color of the outline drawn around the elite
+    pub fn outline_color(&self) -> Color {
+        match self {
+            EliteAffix::Fast => Color::rgb(1.0, 1.0, 0.2),
+            EliteAffix::Regenerating => Color::rgb(0.2, 1.0, 0.3),
+            EliteAffix::Splitting => Color::rgb(1.0, 0.3, 1.0),
+            EliteAffix::Shielded => Color::rgb(0.3, 0.7, 1.0),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Elite {
+    pub affix: EliteAffix,
+    pub max_health: i32,
+    // Hits left before a shielded elite takes damage
+    pub shield: i32,
+    pub regen_timer: Timer,
+}
+
+// Chance for a spawned enemy to be an elite, grows with every minute survived and with the difficulty
+pub fn elite_chance(elapsed_seconds: f32, difficulty: f32) -> f32 {
+    ((0.02 + 0.01 * elapsed_seconds / 60.0) * difficulty).min(0.25)
+}
+
+// Roll whether a newly spawned enemy becomes an elite, and with which affix
+// The chance follows the run clock, so time spent in menus or paused doesn't make elites more likely
+pub fn roll_elite(clock: &RunClock, difficulty: f32, rng: &mut impl Rng) -> Option<EliteAffix> {
+    if rng.gen::<f32>() < elite_chance(clock.elapsed, difficulty) {
+        Some(EliteAffix::random(rng))
+    } else {
+        None
+    }
+}
+
+// Turn the stats of a normal enemy into the stats of an elite
+pub fn promote(enemy: &mut Enemy, affix: EliteAffix) -> Elite {
+    enemy.size *= ELITE_SCALE;
+    enemy.health *= ELITE_HEALTH_MULTIPLIER;
+    enemy.experience *= ELITE_HEALTH_MULTIPLIER;
+    if affix == EliteAffix::Fast {
+        enemy.move_speed *= 1.6;
+    }
+    Elite {
+        affix,
+        max_health: enemy.health,
+        shield: if affix == EliteAffix::Shielded { ELITE_SHIELD_HITS } else { 0 },
+        regen_timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+    }
+}
+
+// The outline is a slightly bigger sprite in the affix color drawn behind the elite
+pub fn spawn_outline(commands: &mut Commands, elite_entity: Entity, size: Vec2, affix: EliteAffix) {
+    commands.entity(elite_entity).with_children(|parent| {
+        parent.spawn(SpriteBundle {
+            sprite: Sprite {
+                color: affix.outline_color(),
+                custom_size: Some(size + Vec2::splat(8.0)),
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, -0.1),
+            ..default()
+        });
+    });
+}
+
+// Regenerating elites heal a tenth of their health every second
+pub fn regenerate_elites(time: Res<Time>, mut elite_query: Query<(&mut Elite, &mut Enemy)>) {
+    for (mut elite, mut enemy) in elite_query.iter_mut() {
+        if elite.affix != EliteAffix::Regenerating {
+            continue;
+        }
+        if elite.regen_timer.tick(time.delta()).just_finished() {
+            enemy.health = (enemy.health + (elite.max_health / 10).max(1)).min(elite.max_health);
+        }
+    }
+}
+
+// Splitting elites leave two normal enemies of their kind behind
+pub fn split_elites(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut death_events: EventReader<EnemyDeathEvent>,
+) {
+    for event in death_events.read() {
+        if event.elite != Some(EliteAffix::Splitting) {
+            continue;
+        }
+        for offset in [Vec2::new(-30.0, 0.0), Vec2::new(30.0, 0.0)] {
+            enemy::spawn_enemy_entity(&mut commands, &asset_server, event.kind, event.position + offset, None);
+        }
+    }
+}
@@ -1,101 +1,255 @@
 use bevy::prelude::*;
-use crate::player;
-use bevy::render::render_resource::Texture;
+use crate::behavior::EnemyBehavior;
+use crate::elite;
+use crate::map::SpawnZone;
+use crate::obstacle::MapBounds;
+use rand::seq::IteratorRandom;
+use crate::spawning::{self, SpawnPattern};
+use crate::stage::{RunClock, SelectedStage};
+use crate::status::{BaseColor, StatusEffects, StatusKind};
+use serde::Deserialize;
 
 
 // Enemy component
 
 #[derive(Component)]
 pub struct Enemy{
+    pub kind: EnemyKind,
     pub move_speed: f32,
     pub size: Vec2,
     pub health: i32,
+    // How much experience the enemy drops when it dies
+    pub experience: i32,
 }
 
+// The different kinds of enemies, each with their own stats
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum EnemyKind {
+    Bat,
+    Skeleton,
+    Brute,
+    Ghost,
+    // Runs away from the player carrying loot
+    Goblin,
+    // Comes for the player once the run is over, it can't be killed
+    Reaper,
+}
+
+impl EnemyKind {
+    pub fn stats(&self) -> Enemy {
+        match self {
+            EnemyKind::Bat => Enemy {
+                kind: *self,
+                move_speed: 100.0,
+                size: Vec2::new(40.0, 40.0),
+                health: 1,
+                experience: 2,
+            },
+            EnemyKind::Skeleton => Enemy {
+                kind: *self,
+                move_speed: 75.0,
+                size: Vec2::new(50.0, 50.0),
+                health: 3,
+                experience: 10,
+            },
+            EnemyKind::Brute => Enemy {
+                kind: *self,
+                move_speed: 50.0,
+                size: Vec2::new(75.0, 75.0),
+                health: 10,
+                experience: 50,
+            },
+            EnemyKind::Ghost => Enemy {
+                kind: *self,
+                move_speed: 120.0,
+                size: Vec2::new(45.0, 45.0),
+                health: 4,
+                experience: 10,
+            },
+            EnemyKind::Goblin => Enemy {
+                kind: *self,
+                move_speed: 90.0,
+                size: Vec2::new(40.0, 40.0),
+                health: 6,
+                experience: 20,
+            },
+            EnemyKind::Reaper => Enemy {
+                kind: *self,
+                move_speed: 250.0,
+                size: Vec2::new(90.0, 90.0),
+                health: 1,
+                experience: 0,
+            },
+        }
+    }
+
+    // How this kind of enemy moves
+    pub fn behavior(&self) -> EnemyBehavior {
+        match self {
+            EnemyKind::Bat => EnemyBehavior::swarm(),
+            EnemyKind::Skeleton => EnemyBehavior::Chase,
+            EnemyKind::Brute => EnemyBehavior::charge(),
+            EnemyKind::Ghost => EnemyBehavior::orbit(),
+            EnemyKind::Goblin => EnemyBehavior::flee(),
+            EnemyKind::Reaper => EnemyBehavior::Chase,
+        }
+    }
+
+    // Unbeatable enemies ignore all damage
+    pub fn unbeatable(&self) -> bool {
+        matches!(self, EnemyKind::Reaper)
+    }
+
+    // Statuses this kind of enemy ignores
+    pub fn immunities(&self) -> &'static [StatusKind] {
+        match self {
+            EnemyKind::Bat => &[],
+            // Skeletons have no blood to poison
+            EnemyKind::Skeleton => &[StatusKind::Poison],
+            // Brutes are too heavy to be slowed down
+            EnemyKind::Brute => &[StatusKind::Slow],
+            // Ghosts have no blood either
+            EnemyKind::Ghost => &[StatusKind::Poison],
+            EnemyKind::Goblin => &[],
+            EnemyKind::Reaper => &[
+                StatusKind::Freeze,
+                StatusKind::Slow,
+                StatusKind::Burn,
+                StatusKind::Poison,
+                StatusKind::Weaken,
+            ],
+        }
+    }
+
+    // All kinds share the same sprite, so they are told apart by their tint
+    pub fn color(&self) -> Color {
+        match self {
+            EnemyKind::Bat => Color::rgb(0.7, 0.7, 1.0),
+            EnemyKind::Skeleton => Color::WHITE,
+            EnemyKind::Brute => Color::rgb(1.0, 0.6, 0.6),
+            EnemyKind::Ghost => Color::rgba(1.0, 1.0, 1.0, 0.6),
+            EnemyKind::Goblin => Color::rgb(0.5, 1.0, 0.5),
+            EnemyKind::Reaper => Color::rgb(0.15, 0.1, 0.2),
+        }
+    }
+}
+
+// Scales how hard the game is, higher values mean more elites
+#[derive(Resource)]
+pub struct Difficulty(pub f32);
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Difficulty(1.0)
+    }
+}
+
+// Bosses are immune to effects that would kill everything on screen at once
+#[derive(Component)]
+pub struct Boss;
+
 #[derive(Component)]
 pub struct SpawnEnemyTimer(pub Timer);
 
-// Spawn enemies on a timer
+// Spawn enemies on a timer, every time the timer finishes a random spawn pattern is used
 pub fn spawn_enemy(
     time: Res<Time>,
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut timer_query: Query<&mut SpawnEnemyTimer>,
     // We'll need to spawn the enemy outside the viewport, so we'll need to query for the camera
-    camera_query: Query<&Transform, With<crate::camera::Camera>>,
+    camera_query: Query<(&OrthographicProjection, &Transform), With<crate::camera::Camera>>,
+    difficulty: Res<Difficulty>,
+    bounds: Res<MapBounds>,
+    zone_query: Query<&SpawnZone>,
+    stage: Res<SelectedStage>,
+    clock: Res<RunClock>,
 ) {
+    let Ok((projection, camera_transform)) = camera_query.get_single() else {
+        return;
+    };
+    let mut rng = rand::thread_rng();
+    // Once the run is over only the reaper comes
+    if clock.elapsed >= stage.0.run_length {
+        return;
+    }
+    // The current wave of the stage decides how fast enemies come
+    let interval = stage.0.wave(clock.elapsed).map(|wave| wave.spawn_interval);
     for mut timer in timer_query.iter_mut() {
+        if let Some(interval) = interval {
+            if timer.0.duration().as_secs_f32() != interval {
+                timer.0.set_duration(std::time::Duration::from_secs_f32(interval));
+            }
+        }
         if timer.0.tick(time.delta()).just_finished() {
-            // Enemy x should be a random location outside the viewport
-            // That means setting the x to either 
-            // So should enemy y
-            let camera_x = camera_query.single().translation.x;
-            let camera_y = camera_query.single().translation.y;
-            let viewport_width = 800.0;
-            let viewport_height = 600.0;
-            let enemy_x = match rand::random(){
-                true => {
-                    camera_x + viewport_width + 50.0
-                },
-                false =>{
-                    camera_x - viewport_width - 50.0
-                }
-            };
-            let enemy_y = match rand::random(){
-                true => {
-                    camera_y + viewport_height + 50.0
-                },
-                false =>{
-                    camera_y - viewport_height - 50.0
-                }
-            };
-            commands.spawn((
-                SpriteBundle {
-                    texture: asset_server.load("branding/howl.png"),
-                    transform: Transform::from_xyz(enemy_x, enemy_y, 0.0),
-                    ..Default::default()
-                },
-                Enemy {
-                    move_speed: 75.0,
-                    size: Vec2::new(50.0, 50.0),
-                    health: 3,
-                },
-            ));
+            let visible = crate::camera::visible_area(projection, camera_transform);
+            let pattern = SpawnPattern::random(&mut rng);
+            // The whole pattern is made of the same kind of enemy
+            let kind = stage.0.random_enemy(&mut rng);
+            // Maps with spawn zones spawn the whole pattern in one zone, preferably one the player can't see
+            let zone = zone_query
+                .iter()
+                .filter(|zone| zone.area.intersect(visible).is_empty())
+                .choose(&mut rng)
+                .or_else(|| zone_query.iter().choose(&mut rng));
+            let points = pattern.points(visible, &mut rng);
+            let size = kind.stats().size;
+            for position in spawning::place_points(points, zone, visible, &bounds, size, &mut rng) {
+                let elite = elite::roll_elite(&clock, difficulty.0, &mut rng);
+                spawn_enemy_entity(&mut commands, &asset_server, kind, position, elite);
+            }
         }
     }
 }
-// Function to make all enemies move towards the player, based on move speed, include delta time for smooth movement
-pub fn move_enemy(
-    time: Res<Time>,
-    player_query: Query<(&player::Player, &Transform), (With<player::Player>, Without<Enemy>)>,
-    mut enemy_query: Query<(&mut Transform, &Enemy), Without<player::Player>>,
-) {
-    for (_, player_transform) in player_query.iter() {
-        for (mut transform, enemy) in enemy_query.iter_mut() {
-            let mut x = transform.translation.x;
-            let mut y = transform.translation.y;
-            let x_diff = player_transform.translation.x - x;
-            let y_diff = player_transform.translation.y - y;
-            let distance = (x_diff.powi(2) + y_diff.powi(2)).sqrt();
-            let x_speed = x_diff / distance * enemy.move_speed;
-            let y_speed = y_diff / distance * enemy.move_speed;
-            x += x_speed * time.delta_seconds();
-            y += y_speed * time.delta_seconds();
-            transform.translation.x = x;
-            transform.translation.y = y;
-        }
+
+// Spawn an enemy of the given kind, promoting it to an elite when an affix is given
+pub fn spawn_enemy_entity(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    kind: EnemyKind,
+    position: Vec2,
+    elite: Option<elite::EliteAffix>,
+) -> Entity {
+    let mut enemy = kind.stats();
+    let elite = elite.map(|affix| elite::promote(&mut enemy, affix));
+    let size = enemy.size;
+    let entity = commands
+        .spawn((
+            SpriteBundle {
+                texture: asset_server.load("branding/howl.png"),
+                transform: Transform::from_xyz(position.x, position.y, 0.0),
+                sprite: Sprite {
+                    color: kind.color(),
+                    custom_size: Some(size),
+                    ..default()
+                },
+                ..Default::default()
+            },
+            enemy,
+            kind.behavior(),
+            StatusEffects::default(),
+            BaseColor(kind.color()),
+        ))
+        .id();
+    if let Some(elite) = elite {
+        elite::spawn_outline(commands, entity, size, elite.affix);
+        commands.entity(entity).insert(elite);
     }
+    entity
 }
-
-// Now we check for collision with projectiles and the enemy, if the enemy is hit, we destroy the enemy and the projectile
+// Now we check for collision with projectiles and the enemy, if the enemy is hit, we destroy the projectile and damage the enemy
 pub fn enemy_collision(
-    asset_server: Res<AssetServer>,
     mut commands: Commands,
-    mut enemy_query: Query<(Entity, &Transform, &Sprite, &Enemy), With<Enemy>>,
-    mut projectile_query: Query<(Entity, &Transform, &Sprite), With<crate::projectile::Projectile>>,
+    enemy_query: Query<(Entity, &Transform, &Enemy)>,
+    mut projectile_query: Query<(Entity, &Transform, &mut crate::projectile::Projectile)>,
+    mut damage_events: EventWriter<crate::damage::DamageEvent>,
+    mut status_events: EventWriter<crate::status::StatusEvent>,
 ) {
-    for (enemy_entity, enemy_transform, _, enemy) in enemy_query.iter_mut() {
-        for (projectile_entity, projectile_transform, _) in projectile_query.iter_mut() {
+    for (projectile_entity, projectile_transform, mut projectile) in projectile_query.iter_mut() {
+        if projectile.spent {
+            continue;
+        }
+        for (enemy_entity, enemy_transform, enemy) in enemy_query.iter() {
             let enemy_x = enemy_transform.translation.x;
             let enemy_y = enemy_transform.translation.y;
             let projectile_x = projectile_transform.translation.x;
@@ -105,31 +259,25 @@ pub fn enemy_collision(
                 && enemy_y - enemy.size.y / 2.0 < projectile_y + 5.0
                 && enemy_y + enemy.size.y / 2.0 > projectile_y - 5.0
             {
+                projectile.spent = true;
                 commands.entity(projectile_entity).despawn();
-                // Check if the enemy is dead
-                if enemy.health <= 1 {
-                    commands.entity(enemy_entity).despawn();
-                    // Then spawn an experience_item
-                    commands.spawn(
-                        (
-                            SpriteBundle {
-                                texture: asset_server.load("branding/gem.png"),
-                                transform: Transform::from_xyz(enemy_x, enemy_y, 0.0),
-                                ..Default::default()
-                            },
-                            crate::experience_item::ExperienceItem {
-                                size: Vec2::new(10.0, 10.0),
-                                experience: 10,
-                            },
-                        )
-                    );
-                } else {
-                    commands.entity(enemy_entity).insert(Enemy {
-                        health: enemy.health - 1,
-                        ..*enemy
+                let (amount, critical) = projectile.roll_damage(&mut rand::thread_rng());
+                damage_events.send(crate::damage::DamageEvent {
+                    target: enemy_entity,
+                    amount,
+                    kind: crate::damage::DamageKind::Physical,
+                    critical,
+                    lethal: false,
+                });
+                if let Some(effect) = projectile.on_hit {
+                    status_events.send(crate::status::StatusEvent {
+                        target: enemy_entity,
+                        effect,
                     });
                 }
+                // A projectile can only hit one enemy
+                break;
             }
         }
     }
-}
\ No newline at end of file
+}
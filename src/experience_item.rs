@@ -1,9 +1,140 @@
 // This is a module for the experience item, when the player walks over it, the player's experience increases
 
+use crate::player::Player;
 use bevy::prelude::*;
 
+// Once there are this many experience items on the map, new drops are merged into a single gem
+pub const MAX_GEMS: usize = 300;
+
+// How fast attracted gems speed up, in units per second squared
+pub const GEM_ACCELERATION: f32 = 600.0;
+
 #[derive(Component)]
 pub struct ExperienceItem {
     pub experience: i32,
     pub size: Vec2,
-}
\ No newline at end of file
+    // Once a gem is attracted it keeps flying towards the player until it is collected
+    pub attracted: bool,
+    pub speed: f32,
+}
+
+// Sent when something drops experience, usually an enemy's loot table
+#[derive(Event)]
+pub struct ExperienceDropEvent {
+    pub position: Vec2,
+    pub experience: i32,
+}
+
+// The gem that collects the experience of every drop over MAX_GEMS
+#[derive(Component)]
+pub struct MergedGem;
+
+// Gems look different depending on how much experience they are worth
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GemTier {
+    Blue,
+    Green,
+    Red,
+}
+
+impl GemTier {
+    pub fn from_experience(experience: i32) -> Self {
+        match experience {
+            experience if experience < 10 => GemTier::Blue,
+            experience if experience < 50 => GemTier::Green,
+            _ => GemTier::Red,
+        }
+    }
+
+    pub fn texture(&self) -> &'static str {
+        match self {
+            GemTier::Blue => "branding/gem.png",
+            GemTier::Green => "branding/GEM_Pixel .png",
+            GemTier::Red => "branding/Ruby_Pixel .png",
+        }
+    }
+}
+
+pub fn spawn_gem(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    position: Vec2,
+    experience: i32,
+) -> Entity {
+    commands
+        .spawn((
+            SpriteBundle {
+                texture: asset_server.load(GemTier::from_experience(experience).texture()),
+                transform: Transform::from_xyz(position.x, position.y, 0.0),
+                ..Default::default()
+            },
+            ExperienceItem {
+                size: Vec2::new(10.0, 10.0),
+                experience,
+                attracted: false,
+                speed: 0.0,
+            },
+        ))
+        .id()
+}
+
+// Every experience drop becomes a gem, once the map is full the experience goes into the merged gem instead
+pub fn drop_experience(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut drop_events: EventReader<ExperienceDropEvent>,
+    gem_query: Query<Entity, With<ExperienceItem>>,
+    mut merged_query: Query<&mut ExperienceItem, With<MergedGem>>,
+) {
+    let mut gem_count = gem_query.iter().count();
+    let mut overflow = 0;
+    let mut overflow_position = None;
+    for event in drop_events.read() {
+        if gem_count < MAX_GEMS {
+            spawn_gem(&mut commands, &asset_server, event.position, event.experience);
+            gem_count += 1;
+        } else {
+            overflow += event.experience;
+            overflow_position.get_or_insert(event.position);
+        }
+    }
+    let Some(position) = overflow_position else {
+        return;
+    };
+    match merged_query.iter_mut().next() {
+        Some(mut merged) => merged.experience += overflow,
+        None => {
+            let gem = spawn_gem(&mut commands, &asset_server, position, overflow);
+            // The merged gem is always a red one, no matter how much it started with
+            commands.entity(gem).insert((
+                MergedGem,
+                asset_server.load::<Image>(GemTier::Red.texture()),
+            ));
+        }
+    }
+}
+
+// Gems inside the player's magnet radius start flying towards the player, speeding up as they go
+pub fn attract_experience(
+    time: Res<Time>,
+    player_query: Query<(&Player, &Transform)>,
+    mut experience_query: Query<(&mut Transform, &mut ExperienceItem), Without<Player>>,
+) {
+    let Ok((player, player_transform)) = player_query.get_single() else {
+        return;
+    };
+    let player_position = player_transform.translation.truncate();
+    for (mut transform, mut experience_item) in experience_query.iter_mut() {
+        let offset = player_position - transform.translation.truncate();
+        if !experience_item.attracted && offset.length() > player.magnet_radius {
+            continue;
+        }
+        experience_item.attracted = true;
+        experience_item.speed += GEM_ACCELERATION * time.delta_seconds();
+        // Don't fly past the player on a slow frame
+        let step = (experience_item.speed * time.delta_seconds()).min(offset.length());
+        let movement = offset.normalize_or_zero() * step;
+        transform.translation.x += movement.x;
+        transform.translation.y += movement.y;
+    }
+}
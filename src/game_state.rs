@@ -0,0 +1,16 @@
+// This is the game state module, gameplay systems only run while the game is in the Playing state
+// The stage is loaded when leaving StageSelect, so it is only loaded once per run
+
+use bevy::prelude::*;
+
+#[derive(States, Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum GameState {
+    // Picking which stage to play, the game starts here
+    #[default]
+    StageSelect,
+    Playing,
+    // A treasure chest is being opened, gameplay is paused until the player closes the reward screen
+    ChestOpening,
+    // The player ran out of health, gameplay stops and the end of run screen is shown
+    GameOver,
+}
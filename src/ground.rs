@@ -0,0 +1,130 @@
+// This is the ground module, the ground is a grid of tiles that moves along with the camera
+// Tiles that fall behind the camera are moved to the other side, so a fixed number of tiles makes an endless field
+
+use crate::camera::Camera;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+// Size of a ground tile in world units
+pub const TILE_SIZE: f32 = 128.0;
+// How many tiles the grid is wide and high, enough to cover a 2560x1440 window with a tile to spare on every side
+pub const GROUND_COLUMNS: i32 = 22;
+pub const GROUND_ROWS: i32 = 14;
+
+// Which set of tiles the ground is made of
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum Biome {
+    #[default]
+    Forest,
+    Desert,
+    Crypt,
+    Snow,
+}
+
+const FOREST_TILES: [Color; 4] = [
+    Color::rgb(0.20, 0.35, 0.18),
+    Color::rgb(0.22, 0.38, 0.20),
+    Color::rgb(0.18, 0.32, 0.16),
+    Color::rgb(0.28, 0.33, 0.18),
+];
+
+const DESERT_TILES: [Color; 3] = [
+    Color::rgb(0.76, 0.65, 0.42),
+    Color::rgb(0.80, 0.69, 0.45),
+    Color::rgb(0.72, 0.60, 0.38),
+];
+
+const CRYPT_TILES: [Color; 4] = [
+    Color::rgb(0.22, 0.21, 0.24),
+    Color::rgb(0.25, 0.24, 0.27),
+    Color::rgb(0.19, 0.18, 0.21),
+    Color::rgb(0.20, 0.22, 0.20),
+];
+
+const SNOW_TILES: [Color; 3] = [
+    Color::rgb(0.88, 0.91, 0.95),
+    Color::rgb(0.84, 0.88, 0.93),
+    Color::rgb(0.92, 0.94, 0.97),
+];
+
+impl Biome {
+    // The tile set of the biome, most tiles use the first color and the others break up the pattern
+    pub fn tiles(&self) -> &'static [Color] {
+        match self {
+            Biome::Forest => &FOREST_TILES,
+            Biome::Desert => &DESERT_TILES,
+            Biome::Crypt => &CRYPT_TILES,
+            Biome::Snow => &SNOW_TILES,
+        }
+    }
+
+    // The tile used for a cell, always the same for the same cell so the ground doesn't flicker when tiles move
+    pub fn tile(&self, cell: IVec2) -> Color {
+        let tiles = self.tiles();
+        let hash = (cell.x as u32).wrapping_mul(73_856_093) ^ (cell.y as u32).wrapping_mul(19_349_663);
+        // At least half of the cells use the first tile
+        let roll = (hash >> 4) as usize % (tiles.len() * 2);
+        tiles[roll.saturating_sub(tiles.len())]
+    }
+}
+
+#[derive(Component)]
+pub struct GroundTile {
+    // The cell of the ground grid the tile currently covers
+    pub cell: IVec2,
+}
+
+// Spawn the ground tiles around the origin, they are moved to the camera in the first update
+pub fn spawn_ground(commands: &mut Commands, biome: Biome) {
+    for row in 0..GROUND_ROWS {
+        for column in 0..GROUND_COLUMNS {
+            let cell = IVec2::new(column - GROUND_COLUMNS / 2, row - GROUND_ROWS / 2);
+            let position = cell.as_vec2() * TILE_SIZE;
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: biome.tile(cell),
+                        custom_size: Some(Vec2::splat(TILE_SIZE)),
+                        ..default()
+                    },
+                    // The ground is below everything else, even hazards
+                    transform: Transform::from_xyz(position.x, position.y, -10.0),
+                    ..default()
+                },
+                GroundTile { cell },
+            ));
+        }
+    }
+}
+
+// Wrap a cell into the window of cells that starts at first and is size cells long
+fn wrap(cell: i32, first: i32, size: i32) -> i32 {
+    first + (cell - first).rem_euclid(size)
+}
+
+// Move every tile that fell behind the camera to the other side of the grid, and retint tiles when the biome changes
+pub fn recycle_ground_tiles(
+    biome: Res<Biome>,
+    camera_query: Query<&Transform, (With<Camera>, Without<GroundTile>)>,
+    mut tile_query: Query<(&mut Transform, &mut Sprite, &mut GroundTile)>,
+) {
+    let Ok(camera_transform) = camera_query.get_single() else {
+        return;
+    };
+    let camera_cell = (camera_transform.translation.truncate() / TILE_SIZE).round().as_ivec2();
+    let first = camera_cell - IVec2::new(GROUND_COLUMNS / 2, GROUND_ROWS / 2);
+    for (mut transform, mut sprite, mut tile) in tile_query.iter_mut() {
+        let cell = IVec2::new(
+            wrap(tile.cell.x, first.x, GROUND_COLUMNS),
+            wrap(tile.cell.y, first.y, GROUND_ROWS),
+        );
+        if cell == tile.cell && !biome.is_changed() {
+            continue;
+        }
+        tile.cell = cell;
+        let position = cell.as_vec2() * TILE_SIZE;
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+        sprite.color = biome.tile(cell);
+    }
+}
@@ -0,0 +1,210 @@
+// This is the hazard module, hazards are damage zones left on the ground like holy water puddles and bombs
+// A hazard waits for its fuse, then damages every enemy inside its radius each tick until its lifetime is over
+
+use crate::damage::{DamageEvent, DamageKind};
+use crate::enemy::Enemy;
+use crate::player::Player;
+use crate::prop::Destructible;
+use crate::status::{StatusEffect, StatusEvent, StatusKind};
+use bevy::prelude::*;
+use bevy::sprite::MaterialMesh2dBundle;
+use rand::seq::IteratorRandom;
+use rand::Rng;
+
+// Where a hazard weapon drops its hazards
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HazardPlacement {
+    // On top of a random enemy, falls back to the player's position when there are no enemies
+    AtEnemy,
+    // At a random position within the given distance of the player
+    NearPlayer { distance: f32 },
+}
+
+// A weapon that drops hazards instead of firing projectiles
+#[derive(Component)]
+pub struct HazardWeapon {
+    pub name: String,
+    pub placement: HazardPlacement,
+    pub radius: f32,
+    pub damage: i32,
+    pub damage_kind: DamageKind,
+    // Seconds before the hazard starts doing damage
+    pub fuse: f32,
+    // Seconds the hazard keeps doing damage after the fuse, zero means it only hits once
+    pub duration: f32,
+    // Seconds between damage ticks
+    pub tick_rate: f32,
+    pub color: Color,
+    // Status applied to every enemy the hazard hits
+    pub on_hit: Option<StatusEffect>,
+    pub slot: u32,
+}
+
+#[derive(Component)]
+pub struct HazardTimer(pub Timer);
+
+#[derive(Component)]
+pub struct Hazard {
+    pub radius: f32,
+    pub damage: i32,
+    pub damage_kind: DamageKind,
+    pub fuse: Timer,
+    pub tick: Timer,
+    pub lifetime: Timer,
+    pub on_hit: Option<StatusEffect>,
+}
+
+// A puddle that damages enemies standing in it for a few seconds
+pub fn holy_water() -> HazardWeapon {
+    HazardWeapon {
+        name: "Holy Water".to_string(),
+        placement: HazardPlacement::AtEnemy,
+        radius: 50.0,
+        damage: 1,
+        damage_kind: DamageKind::Holy,
+        fuse: 0.0,
+        duration: 3.0,
+        tick_rate: 0.5,
+        color: Color::rgba(0.3, 0.5, 1.0, 0.5),
+        // Enemies wading through the water are slowed down
+        on_hit: Some(StatusEffect {
+            kind: StatusKind::Slow,
+            duration: 1.0,
+            potency: 0.5,
+        }),
+        slot: 0,
+    }
+}
+
+// A bomb that explodes after a short fuse, hitting everything around it once
+pub fn bomb() -> HazardWeapon {
+    HazardWeapon {
+        name: "Bomb".to_string(),
+        placement: HazardPlacement::NearPlayer { distance: 200.0 },
+        radius: 80.0,
+        damage: 3,
+        damage_kind: DamageKind::Fire,
+        fuse: 1.5,
+        duration: 0.0,
+        tick_rate: 1.0,
+        color: Color::rgba(1.0, 0.4, 0.1, 0.5),
+        on_hit: Some(StatusEffect {
+            kind: StatusKind::Burn,
+            duration: 3.0,
+            potency: 1.0,
+        }),
+        slot: 0,
+    }
+}
+
+// Drop a hazard for every hazard weapon whose timer is finished
+pub fn drop_hazards(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut hazard_timer_query: Query<(&mut HazardTimer, &HazardWeapon)>,
+    player_query: Query<&Transform, With<Player>>,
+    enemy_query: Query<&Transform, With<Enemy>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let mut rng = rand::thread_rng();
+    for (mut timer, weapon) in hazard_timer_query.iter_mut() {
+        if !timer.0.tick(time.delta()).just_finished() {
+            continue;
+        }
+        let position = match weapon.placement {
+            HazardPlacement::AtEnemy => enemy_query
+                .iter()
+                .choose(&mut rng)
+                .unwrap_or(player_transform)
+                .translation
+                .truncate(),
+            HazardPlacement::NearPlayer { distance } => {
+                let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+                let offset = Vec2::from_angle(angle) * rng.gen_range(0.0..distance);
+                player_transform.translation.truncate() + offset
+            }
+        };
+        commands.spawn((
+            MaterialMesh2dBundle {
+                mesh: meshes.add(shape::Circle::new(weapon.radius).into()).into(),
+                material: materials.add(ColorMaterial::from(weapon.color)),
+                // Hazards lie on the ground, below the player and enemies
+                transform: Transform::from_xyz(position.x, position.y, -1.0),
+                ..default()
+            },
+            Hazard {
+                radius: weapon.radius,
+                damage: weapon.damage,
+                damage_kind: weapon.damage_kind,
+                fuse: Timer::from_seconds(weapon.fuse, TimerMode::Once),
+                tick: Timer::from_seconds(weapon.tick_rate, TimerMode::Repeating),
+                lifetime: Timer::from_seconds(weapon.duration, TimerMode::Once),
+                on_hit: weapon.on_hit,
+            },
+        ));
+    }
+}
+
+// Tick every hazard, damaging the enemies inside of it and despawning it once its lifetime is over
+pub fn update_hazards(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut hazard_query: Query<(Entity, &Transform, &mut Hazard)>,
+    enemy_query: Query<(Entity, &Transform, &Enemy)>,
+    prop_query: Query<(Entity, &Transform), With<Destructible>>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut status_events: EventWriter<StatusEvent>,
+) {
+    for (hazard_entity, hazard_transform, mut hazard) in hazard_query.iter_mut() {
+        hazard.fuse.tick(time.delta());
+        if !hazard.fuse.finished() {
+            continue;
+        }
+        // The first hit happens as soon as the fuse is done, then once every tick
+        let hits = if hazard.fuse.just_finished() {
+            true
+        } else {
+            hazard.tick.tick(time.delta()).just_finished()
+        };
+        if hits {
+            let center = hazard_transform.translation.truncate();
+            for (enemy_entity, enemy_transform, enemy) in enemy_query.iter() {
+                let distance = enemy_transform.translation.truncate().distance(center);
+                if distance <= hazard.radius + enemy.size.x / 2.0 {
+                    damage_events.send(DamageEvent {
+                        target: enemy_entity,
+                        amount: hazard.damage,
+                        kind: hazard.damage_kind,
+                        critical: false,
+                        lethal: false,
+                    });
+                    if let Some(effect) = hazard.on_hit {
+                        status_events.send(StatusEvent {
+                            target: enemy_entity,
+                            effect,
+                        });
+                    }
+                }
+            }
+            // Hazards break props standing in them too
+            for (prop_entity, prop_transform) in prop_query.iter() {
+                if prop_transform.translation.truncate().distance(center) <= hazard.radius {
+                    damage_events.send(DamageEvent {
+                        target: prop_entity,
+                        amount: hazard.damage,
+                        kind: hazard.damage_kind,
+                        critical: false,
+                        lethal: false,
+                    });
+                }
+            }
+        }
+        if hazard.lifetime.tick(time.delta()).finished() {
+            commands.entity(hazard_entity).despawn();
+        }
+    }
+}
@@ -1,6 +1,32 @@
+// Bevy systems take their queries as arguments, so long signatures and nested query types are expected
+#![allow(clippy::type_complexity, clippy::too_many_arguments)]
+
+pub mod aim;
+pub mod behavior;
 pub mod camera;
+pub mod chest;
+pub mod damage;
+pub mod data;
+pub mod damage_number;
+pub mod ground;
+pub mod hazard;
+pub mod loot;
+pub mod map;
+pub mod obstacle;
+pub mod pathfinding;
+pub mod pickup;
 pub mod player;
+pub mod elite;
 pub mod enemy;
+pub mod progress;
 pub mod projectile;
+pub mod prop;
+pub mod weapon;
 pub mod experience_item;
+pub mod game_state;
+pub mod run;
+pub mod settings;
+pub mod spawning;
+pub mod stage;
+pub mod status;
 pub mod ui;
\ No newline at end of file
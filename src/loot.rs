@@ -0,0 +1,202 @@
+// This is the loot module, every kind of enemy has a loot table deciding what it drops when it dies
+// Props that can be broken have their own tables
+// The tables are loaded from assets/data so they can be tuned without recompiling
+
+use crate::damage::EnemyDeathEvent;
+use crate::data;
+use crate::enemy::EnemyKind;
+use crate::experience_item::ExperienceDropEvent;
+use crate::pickup::{self, PickupKind};
+use crate::player::Player;
+use crate::prop::{PropDestroyedEvent, PropKind};
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+pub const LOOT_TABLES_PATH: &str = "assets/data/loot_tables.ron";
+pub const PROP_LOOT_TABLES_PATH: &str = "assets/data/prop_loot_tables.ron";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum LootDrop {
+    Nothing,
+    // An experience gem worth the enemy's experience
+    Experience,
+    // A coin worth this much gold
+    Gold(u32),
+    Magnet,
+    Chest,
+    // Floor food that heals this much health
+    Food(i32),
+    Bomb,
+    Clock,
+    Cross,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct LootEntry {
+    pub drop: LootDrop,
+    pub weight: f32,
+    // Rare entries become more likely the more luck the player has
+    #[serde(default)]
+    pub rare: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct LootTable {
+    // Dropped every time
+    #[serde(default)]
+    pub always: Vec<LootDrop>,
+    // One of these is picked at random
+    #[serde(default)]
+    pub roll: Vec<LootEntry>,
+}
+
+impl LootTable {
+    // Pick one of the roll entries, luck multiplies the weight of rare entries
+    pub fn roll(&self, luck: f32, rng: &mut impl Rng) -> LootDrop {
+        let weight = |entry: &LootEntry| {
+            if entry.rare {
+                entry.weight * luck
+            } else {
+                entry.weight
+            }
+        };
+        let total: f32 = self.roll.iter().map(weight).sum();
+        if total <= 0.0 {
+            return LootDrop::Nothing;
+        }
+        let mut pick = rng.gen_range(0.0..total);
+        for entry in &self.roll {
+            if pick < weight(entry) {
+                return entry.drop;
+            }
+            pick -= weight(entry);
+        }
+        // Floating point rounding can leave us just past the last entry
+        self.roll.last().map_or(LootDrop::Nothing, |entry| entry.drop)
+    }
+
+    // Everything an enemy drops when it dies
+    pub fn drops(&self, luck: f32, rng: &mut impl Rng) -> Vec<LootDrop> {
+        let mut drops = self.always.clone();
+        drops.push(self.roll(luck, rng));
+        drops
+    }
+}
+
+#[derive(Resource, Deserialize)]
+#[serde(transparent)]
+pub struct LootTables(pub HashMap<EnemyKind, LootTable>);
+
+// What kinds without a table drop, like the reaper which can't be killed anyway
+static NO_LOOT: LootTable = LootTable {
+    always: Vec::new(),
+    roll: Vec::new(),
+};
+
+impl LootTables {
+    pub fn table(&self, kind: EnemyKind) -> &LootTable {
+        self.0.get(&kind).unwrap_or(&NO_LOOT)
+    }
+
+    pub fn from_ron(source: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(source)
+    }
+}
+
+// Loot tables for the props that can be broken
+#[derive(Resource, Deserialize)]
+#[serde(transparent)]
+pub struct PropLootTables(pub HashMap<PropKind, LootTable>);
+
+impl PropLootTables {
+    pub fn from_ron(source: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(source)
+    }
+}
+
+// Read the loot tables from disk when the game starts, without them nothing drops any loot
+pub fn load_loot_tables(mut commands: Commands) {
+    let tables = data::read(LOOT_TABLES_PATH, LootTables::from_ron).unwrap_or_else(|error| {
+        error!("{}", error);
+        LootTables(HashMap::new())
+    });
+    commands.insert_resource(tables);
+    let tables = data::read(PROP_LOOT_TABLES_PATH, PropLootTables::from_ron).unwrap_or_else(|error| {
+        error!("{}", error);
+        PropLootTables(HashMap::new())
+    });
+    commands.insert_resource(tables);
+}
+
+// Roll the loot table of every enemy that died this frame and spawn what it dropped
+pub fn drop_loot(
+    mut commands: Commands,
+    loot_tables: Res<LootTables>,
+    mut death_events: EventReader<EnemyDeathEvent>,
+    mut experience_events: EventWriter<ExperienceDropEvent>,
+    player_query: Query<&Player>,
+) {
+    let luck = player_query.get_single().map_or(1.0, |player| player.luck);
+    let mut rng = rand::thread_rng();
+    for event in death_events.read() {
+        let table = loot_tables.table(event.kind);
+        let mut drops = table.drops(luck, &mut rng);
+        // Elites always drop a chest on top of their normal loot
+        if event.elite.is_some() {
+            drops.push(LootDrop::Chest);
+        }
+        for drop in drops {
+            spawn_drop(&mut commands, &mut experience_events, drop, event.position, event.experience, &mut rng);
+        }
+    }
+}
+
+// Roll the loot table of every prop that broke this frame, props don't give experience
+pub fn drop_prop_loot(
+    mut commands: Commands,
+    loot_tables: Res<PropLootTables>,
+    mut destroyed_events: EventReader<PropDestroyedEvent>,
+    mut experience_events: EventWriter<ExperienceDropEvent>,
+    player_query: Query<&Player>,
+) {
+    let luck = player_query.get_single().map_or(1.0, |player| player.luck);
+    let mut rng = rand::thread_rng();
+    for event in destroyed_events.read() {
+        let Some(table) = loot_tables.0.get(&event.kind) else {
+            continue;
+        };
+        for drop in table.drops(luck, &mut rng) {
+            spawn_drop(&mut commands, &mut experience_events, drop, event.position, 0, &mut rng);
+        }
+    }
+}
+
+// Spawn a single drop, experience is what an experience drop is worth
+fn spawn_drop(
+    commands: &mut Commands,
+    experience_events: &mut EventWriter<ExperienceDropEvent>,
+    drop: LootDrop,
+    position: Vec2,
+    experience: i32,
+    rng: &mut impl Rng,
+) {
+    // Spread the drops out a little so they don't all land on top of each other
+    let offset = Vec2::new(rng.gen_range(-15.0..15.0), rng.gen_range(-15.0..15.0));
+    match drop {
+        LootDrop::Nothing => {}
+        LootDrop::Experience => {
+            if experience > 0 {
+                experience_events.send(ExperienceDropEvent { position, experience });
+            }
+        }
+        LootDrop::Gold(amount) => pickup::spawn_pickup(commands, PickupKind::Gold(amount), position + offset),
+        LootDrop::Magnet => pickup::spawn_pickup(commands, PickupKind::Magnet, position + offset),
+        LootDrop::Food(amount) => pickup::spawn_pickup(commands, PickupKind::Food(amount), position + offset),
+        LootDrop::Bomb => pickup::spawn_pickup(commands, PickupKind::Bomb, position + offset),
+        LootDrop::Clock => pickup::spawn_pickup(commands, PickupKind::Clock, position + offset),
+        LootDrop::Cross => pickup::spawn_pickup(commands, PickupKind::Cross, position + offset),
+        LootDrop::Chest => pickup::spawn_pickup(commands, PickupKind::Chest, position + offset),
+    }
+}
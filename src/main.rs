@@ -2,11 +2,33 @@ use bevy::log::LogPlugin;
 use bevy::prelude::*;
 
 // import lib.rs
+use rust_survivors::aim;
+use rust_survivors::behavior;
 use rust_survivors::camera;
+use rust_survivors::chest;
+use rust_survivors::damage;
+use rust_survivors::damage_number;
+use rust_survivors::ground;
+use rust_survivors::hazard;
+use rust_survivors::elite;
 use rust_survivors::enemy;
+use rust_survivors::experience_item;
+use rust_survivors::game_state::GameState;
+use rust_survivors::loot;
+use rust_survivors::map;
+use rust_survivors::obstacle;
+use rust_survivors::pathfinding;
+use rust_survivors::pickup;
 use rust_survivors::player;
+use rust_survivors::progress;
 use rust_survivors::projectile;
+use rust_survivors::prop;
+use rust_survivors::run;
+use rust_survivors::settings;
+use rust_survivors::stage;
+use rust_survivors::status;
 use rust_survivors::ui;
+use rust_survivors::weapon;
 // import player module
 fn main() {
     App::new()
@@ -16,27 +38,130 @@ fn main() {
                 level: bevy::log::Level::INFO,
             }),
         )
-        .add_systems(Startup, setup)
+        .add_state::<GameState>()
+        .add_event::<damage::DamageEvent>()
+        .add_event::<status::StatusEvent>()
+        .add_event::<damage::DamageDealtEvent>()
+        .add_event::<damage::EnemyDeathEvent>()
+        .add_event::<prop::PropDestroyedEvent>()
+        .add_event::<experience_item::ExperienceDropEvent>()
+        .init_resource::<camera::CursorPosition>()
+        .init_resource::<enemy::Difficulty>()
+        .init_resource::<settings::Settings>()
+        .init_resource::<aim::AimDirection>()
+        .init_resource::<pathfinding::FlowField>()
+        .init_resource::<ground::Biome>()
+        .init_resource::<obstacle::MapBounds>()
+        .init_resource::<stage::RunClock>()
+        .init_resource::<run::RunStats>()
+        .init_resource::<weapon::NextSlot>()
+        .add_systems(
+            Startup,
+            (setup, loot::load_loot_tables, stage::load_stages, progress::load_progress),
+        )
+        .add_systems(OnEnter(GameState::StageSelect), stage::open_stage_select)
+        .add_systems(
+            Update,
+            stage::navigate_stage_select.run_if(in_state(GameState::StageSelect)),
+        )
+        .add_systems(
+            OnExit(GameState::StageSelect),
+            (stage::close_stage_select, map::load_map),
+        )
         .add_systems(
             Update,
             (
-                player::sprite_movement,
-                enemy::move_enemy,
-                camera::follow_player,
-                projectile::fire_projectile,
-                projectile::update_projectiles,
-                enemy::enemy_collision,
-                enemy::spawn_enemy,
-                player::player_collision,
-                player::update_hit_timer,
-                player::experience_collision
+                // Movement and input
+                (
+                    stage::tick_run_clock,
+                    player::sprite_movement,
+                    pathfinding::update_flow_field,
+                    behavior::chase_player,
+                    behavior::charge_player,
+                    behavior::orbit_player,
+                    behavior::swarm_player,
+                    behavior::flee_player,
+                    obstacle::block_enemies,
+                    camera::follow_player,
+                    ground::recycle_ground_tiles,
+                    camera::update_cursor_position,
+                    settings::toggle_manual_aim,
+                    aim::update_aim_direction,
+                )
+                    .chain(),
+                // Weapons and damage
+                (
+                    projectile::fire_projectile,
+                    projectile::fire_queued_shots,
+                    projectile::update_projectiles,
+                    enemy::enemy_collision,
+                    prop::prop_collision,
+                    hazard::drop_hazards,
+                    hazard::update_hazards,
+                    damage::apply_damage,
+                    status::apply_status,
+                    status::tick_status_effects,
+                    status::tint_status_effects,
+                )
+                    .chain(),
+                // Drops and spawning
+                (
+                    loot::drop_loot,
+                    loot::drop_prop_loot,
+                    elite::split_elites,
+                    elite::regenerate_elites,
+                    experience_item::drop_experience,
+                    enemy::spawn_enemy,
+                )
+                    .chain(),
+                // Player collisions and pickups
+                (
+                    player::player_collision,
+                    player::update_hit_timer,
+                    player::regenerate_health,
+                    experience_item::attract_experience,
+                    player::experience_collision,
+                    pickup::pickup_collision,
+                    progress::record_progress,
+                    run::count_kills,
+                    run::end_run,
+                    run::check_player_death,
+                )
+                    .chain(),
+                // HUD
+                (
+                    ui::update_run_timer,
+                    ui::update_health_text,
+                    ui::update_experience_text,
+                    ui::update_level_text,
+                    ui::update_gold_text,
+                    ui::update_kill_text,
+                    ui::update_item_bar,
+                    ui::attach_health_bar,
+                    ui::update_health_bar,
+                    ui::update_experience_bar,
+                    damage_number::spawn_damage_numbers,
+                    damage_number::animate_damage_numbers,
+                )
+                    .chain(),
             )
-                .chain(),
+                .chain()
+                .run_if(in_state(GameState::Playing)),
         )
+        // The UI scales with the window on every screen, not just while playing
+        .add_systems(Update, ui::scale_ui)
+        .add_systems(OnEnter(GameState::ChestOpening), chest::open_chest)
+        .add_systems(
+            Update,
+            chest::animate_chest.run_if(in_state(GameState::ChestOpening)),
+        )
+        .add_systems(OnExit(GameState::ChestOpening), chest::close_chest)
+        .add_systems(OnEnter(GameState::GameOver), run::open_game_over_screen)
+        .add_systems(Last, progress::save_progress_on_exit)
         .run();
 }
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, mut next_slot: ResMut<weapon::NextSlot>) {
     camera::create_camera(&mut commands);
     commands.spawn((
         SpriteBundle {
@@ -50,24 +175,43 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
             ..default()
         },
         player::Player {
-            health: 100,
             position: (100., 0.),
-            move_speed: 100.,
-            fire_rate: 4.,
-            size: Vec2::new(50., 50.),
-            experience: 0,
-            level: 1,
-            experience_to_next_level: 10,
-            recently_hit: false,
+            regen: 0.2,
+            ..default()
         },
+        status::StatusEffects::default(),
     ));
 
 
-    // Setup projectile firing timer, every 4 seconds
-    commands.spawn(projectile::ProjectileTimer(Timer::from_seconds(
-        4.0,
-        TimerMode::Repeating,
-    )));
+    // Setup projectile firing timer for the starting wand, every 4 seconds
+    commands.spawn((
+        projectile::ProjectileTimer(Timer::from_seconds(4.0, TimerMode::Repeating)),
+        weapon::Weapon { slot: next_slot.take(), ..weapon::magic_wand() },
+    ));
+
+    // The boomerang fires every 3 seconds
+    commands.spawn((
+        projectile::ProjectileTimer(Timer::from_seconds(3.0, TimerMode::Repeating)),
+        weapon::Weapon { slot: next_slot.take(), ..weapon::boomerang() },
+    ));
+
+    // Knives are thrown where the player is facing every 2 seconds
+    commands.spawn((
+        projectile::ProjectileTimer(Timer::from_seconds(2.0, TimerMode::Repeating)),
+        weapon::Weapon { slot: next_slot.take(), ..weapon::knife() },
+    ));
+
+    // Holy water is dropped on an enemy every 5 seconds
+    commands.spawn((
+        hazard::HazardTimer(Timer::from_seconds(5.0, TimerMode::Repeating)),
+        hazard::HazardWeapon { slot: next_slot.take(), ..hazard::holy_water() },
+    ));
+
+    // A bomb is dropped near the player every 7 seconds
+    commands.spawn((
+        hazard::HazardTimer(Timer::from_seconds(7.0, TimerMode::Repeating)),
+        hazard::HazardWeapon { slot: next_slot.take(), ..hazard::bomb() },
+    ));
 
     // Setup enemy spawn timer, every 2 seconds
     commands.spawn(enemy::SpawnEnemyTimer(Timer::from_seconds(
@@ -0,0 +1,255 @@
+// This is the map module, stage layouts are made in the Tiled map editor and exported as JSON
+// Every object on an object layer becomes an entity when the stage starts, its type decides what it becomes:
+// obstacle, spawn_zone, light or prop, with the details stored in the object's custom properties
+// The map is centered on the origin, so the player starts in the middle of it
+
+use crate::data;
+use crate::ground::{self, Biome};
+use crate::obstacle::{self, MapBounds, ObstacleKind};
+use crate::prop::{self, PropKind};
+use crate::stage::SelectedStage;
+use bevy::prelude::*;
+use bevy::sprite::MaterialMesh2dBundle;
+use rand::Rng;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+// An area enemies come in from, maps without spawn zones spawn enemies around the edge of the screen
+#[derive(Component)]
+pub struct SpawnZone {
+    pub area: Rect,
+}
+
+impl SpawnZone {
+    pub fn random_point(&self, rng: &mut impl Rng) -> Vec2 {
+        Vec2::new(
+            rng.gen_range(self.area.min.x..=self.area.max.x),
+            rng.gen_range(self.area.min.y..=self.area.max.y),
+        )
+    }
+}
+
+// A pool of light on the ground
+#[derive(Component)]
+pub struct Light {
+    pub radius: f32,
+    pub color: Color,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MapObjectKind {
+    Obstacle(ObstacleKind),
+    SpawnZone,
+    Light { radius: f32, color: Color },
+    Prop(PropKind),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapObject {
+    pub kind: MapObjectKind,
+    // Center of the object in world coordinates
+    pub position: Vec2,
+    pub size: Vec2,
+}
+
+// A map file turned into what the game needs from it
+#[derive(Clone, Debug)]
+pub struct MapDefinition {
+    // Size of the map in world units
+    pub size: Vec2,
+    // Bounded maps keep the player and the camera inside of them, the others are endless
+    pub bounded: bool,
+    pub biome: Biome,
+    pub objects: Vec<MapObject>,
+}
+
+impl MapDefinition {
+    pub fn from_json(source: &str) -> Result<Self, String> {
+        let map: TiledMap = serde_json::from_str(source).map_err(|error| error.to_string())?;
+        let size = Vec2::new(
+            (map.width * map.tilewidth) as f32,
+            (map.height * map.tileheight) as f32,
+        );
+        let mut objects = Vec::new();
+        for object in map.layers.iter().flat_map(|layer| layer.objects.iter()) {
+            objects.push(object.to_map_object(size)?);
+        }
+        Ok(MapDefinition {
+            size,
+            bounded: property(&map.properties, "bounded")?.unwrap_or(false),
+            biome: property(&map.properties, "biome")?.unwrap_or_default(),
+            objects,
+        })
+    }
+
+    // An endless map with nothing on it
+    pub fn empty() -> Self {
+        MapDefinition {
+            size: Vec2::ZERO,
+            bounded: false,
+            biome: Biome::default(),
+            objects: Vec::new(),
+        }
+    }
+
+    pub fn bounds(&self) -> MapBounds {
+        if self.bounded {
+            MapBounds(Some(Rect::from_center_size(Vec2::ZERO, self.size)))
+        } else {
+            MapBounds(None)
+        }
+    }
+}
+
+// The parts of the Tiled JSON format we use
+#[derive(Deserialize)]
+struct TiledMap {
+    width: u32,
+    height: u32,
+    tilewidth: u32,
+    tileheight: u32,
+    #[serde(default)]
+    properties: Vec<TiledProperty>,
+    layers: Vec<TiledLayer>,
+}
+
+#[derive(Deserialize)]
+struct TiledLayer {
+    // Only object layers have objects, tile layers are ignored
+    #[serde(default)]
+    objects: Vec<TiledObject>,
+}
+
+#[derive(Deserialize)]
+struct TiledProperty {
+    name: String,
+    value: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct TiledObject {
+    // Tiled 1.9 calls this class, other versions call it type
+    #[serde(alias = "class")]
+    r#type: String,
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+    #[serde(default)]
+    properties: Vec<TiledProperty>,
+}
+
+impl TiledObject {
+    // Tiled puts the origin in the top left corner with y going down, and x and y are the top left of the object
+    fn to_map_object(&self, map_size: Vec2) -> Result<MapObject, String> {
+        let size = Vec2::new(self.width, self.height);
+        let position = Vec2::new(
+            self.x + size.x / 2.0 - map_size.x / 2.0,
+            map_size.y / 2.0 - (self.y + size.y / 2.0),
+        );
+        let kind = match self.r#type.as_str() {
+            "obstacle" => {
+                let kind: ObstacleKind = required(&self.properties, "kind")?;
+                // Trees and rocks can be placed as points, then they get their usual size
+                return Ok(MapObject {
+                    kind: MapObjectKind::Obstacle(kind),
+                    position,
+                    size: if size == Vec2::ZERO { kind.size() } else { size },
+                });
+            }
+            "spawn_zone" => MapObjectKind::SpawnZone,
+            "light" => MapObjectKind::Light {
+                radius: property(&self.properties, "radius")?.unwrap_or(150.0),
+                color: match property::<String>(&self.properties, "color")? {
+                    Some(color) => parse_color(&color)?,
+                    None => Color::rgba(1.0, 0.8, 0.5, 0.2),
+                },
+            },
+            "prop" => {
+                let kind: PropKind = required(&self.properties, "kind")?;
+                return Ok(MapObject {
+                    kind: MapObjectKind::Prop(kind),
+                    position,
+                    size: kind.size(),
+                });
+            }
+            other => return Err(format!("Unknown map object type {:?}", other)),
+        };
+        Ok(MapObject { kind, position, size })
+    }
+}
+
+// Read a custom property, None when the object doesn't have it
+fn property<T: DeserializeOwned>(properties: &[TiledProperty], name: &str) -> Result<Option<T>, String> {
+    properties
+        .iter()
+        .find(|property| property.name == name)
+        .map(|property| {
+            serde_json::from_value(property.value.clone())
+                .map_err(|error| format!("Bad value for property {:?}: {}", name, error))
+        })
+        .transpose()
+}
+
+fn required<T: DeserializeOwned>(properties: &[TiledProperty], name: &str) -> Result<T, String> {
+    property(properties, name)?.ok_or_else(|| format!("Missing property {:?}", name))
+}
+
+// Tiled writes colors as #AARRGGBB, or #RRGGBB when they are fully opaque
+fn parse_color(color: &str) -> Result<Color, String> {
+    let hex = color.trim_start_matches('#');
+    let value = u32::from_str_radix(hex, 16).map_err(|_| format!("Bad color {:?}", color))?;
+    let channel = |shift: u32| ((value >> shift) & 0xff) as f32 / 255.0;
+    match hex.len() {
+        6 => Ok(Color::rgb(channel(16), channel(8), channel(0))),
+        8 => Ok(Color::rgba(channel(16), channel(8), channel(0), channel(24))),
+        _ => Err(format!("Bad color {:?}", color)),
+    }
+}
+
+// Read the map of the selected stage when it starts and spawn everything on it, along with the ground
+pub fn load_map(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    stage: Res<SelectedStage>,
+) {
+    // A map that can't be loaded leaves an empty endless field, so the stage is still playable
+    let map = data::read(&stage.0.map, MapDefinition::from_json).unwrap_or_else(|error| {
+        error!("{}", error);
+        MapDefinition::empty()
+    });
+    let biome = stage.0.ground.unwrap_or(map.biome);
+    ground::spawn_ground(&mut commands, biome);
+    for object in &map.objects {
+        match object.kind {
+            MapObjectKind::Obstacle(kind) => {
+                obstacle::spawn_obstacle(&mut commands, kind, object.position, object.size);
+            }
+            MapObjectKind::SpawnZone => {
+                commands.spawn(SpawnZone {
+                    area: Rect::from_center_size(object.position, object.size),
+                });
+            }
+            MapObjectKind::Light { radius, color } => {
+                commands.spawn((
+                    MaterialMesh2dBundle {
+                        mesh: meshes.add(shape::Circle::new(radius).into()).into(),
+                        material: materials.add(ColorMaterial::from(color)),
+                        // Light falls on the ground, below hazards
+                        transform: Transform::from_xyz(object.position.x, object.position.y, -2.0),
+                        ..default()
+                    },
+                    Light { radius, color },
+                ));
+            }
+            MapObjectKind::Prop(kind) => {
+                prop::spawn_prop(&mut commands, kind, object.position, object.size);
+            }
+        }
+    }
+    commands.insert_resource(map.bounds());
+    commands.insert_resource(biome);
+}
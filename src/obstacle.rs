@@ -0,0 +1,144 @@
+// This is the obstacle module, obstacles are things in the world that can't be walked through like walls, trees and rocks
+// Maps can also have bounds, then the player, the enemies and the camera all stay inside of them
+use crate::enemy::Enemy;
+use crate::player::Player;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum ObstacleKind {
+    Wall,
+    Tree,
+    Rock,
+}
+
+impl ObstacleKind {
+    // Trees and rocks are always the same size, walls are sized by the map
+    pub fn size(&self) -> Vec2 {
+        match self {
+            ObstacleKind::Wall => Vec2::new(50.0, 50.0),
+            ObstacleKind::Tree => Vec2::new(60.0, 60.0),
+            ObstacleKind::Rock => Vec2::new(40.0, 35.0),
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            ObstacleKind::Wall => Color::rgb(0.35, 0.28, 0.22),
+            ObstacleKind::Tree => Color::rgb(0.10, 0.25, 0.10),
+            ObstacleKind::Rock => Color::rgb(0.45, 0.45, 0.48),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Obstacle {
+    pub size: Vec2,
+}
+
+impl Obstacle {
+    // The area the obstacle covers when it stands at the given position
+    pub fn area(&self, position: Vec2) -> Rect {
+        Rect::from_center_size(position, self.size)
+    }
+}
+
+// The area the map is limited to, None for endless maps
+#[derive(Resource, Default)]
+pub struct MapBounds(pub Option<Rect>);
+
+impl MapBounds {
+    // Keep a box of the given size inside of the bounds
+    pub fn clamp(&self, position: Vec2, size: Vec2) -> Vec2 {
+        let Some(bounds) = self.0 else {
+            return position;
+        };
+        let half = size / 2.0;
+        Vec2::new(
+            clamp_axis(position.x, bounds.min.x + half.x, bounds.max.x - half.x),
+            clamp_axis(position.y, bounds.min.y + half.y, bounds.max.y - half.y),
+        )
+    }
+}
+
+// Clamp between min and max, centering when there is no room at all
+fn clamp_axis(value: f32, min: f32, max: f32) -> f32 {
+    if min > max {
+        return (min + max) / 2.0;
+    }
+    value.clamp(min, max)
+}
+
+// Push a box at position out of an obstacle area the shortest way, the position is unchanged when they don't overlap
+pub fn push_out(position: Vec2, size: Vec2, area: Rect) -> Vec2 {
+    let half = size / 2.0;
+    let left = position.x + half.x - area.min.x;
+    let right = area.max.x - (position.x - half.x);
+    let down = position.y + half.y - area.min.y;
+    let up = area.max.y - (position.y - half.y);
+    if left <= 0.0 || right <= 0.0 || down <= 0.0 || up <= 0.0 {
+        return position;
+    }
+    let smallest = left.min(right).min(down).min(up);
+    if smallest == left {
+        Vec2::new(position.x - left, position.y)
+    } else if smallest == right {
+        Vec2::new(position.x + right, position.y)
+    } else if smallest == down {
+        Vec2::new(position.x, position.y - down)
+    } else {
+        Vec2::new(position.x, position.y + up)
+    }
+}
+
+// Push a box out of every obstacle and keep it inside of the map bounds
+pub fn resolve_collisions(position: Vec2, size: Vec2, obstacles: &[Rect], bounds: &MapBounds) -> Vec2 {
+    let position = obstacles
+        .iter()
+        .fold(position, |position, area| push_out(position, size, *area));
+    bounds.clamp(position, size)
+}
+
+pub fn spawn_obstacle(commands: &mut Commands, kind: ObstacleKind, position: Vec2, size: Vec2) -> Entity {
+    commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: kind.color(),
+                    custom_size: Some(size),
+                    ..default()
+                },
+                // Obstacles are drawn above the ground and hazards, but below the player and enemies
+                transform: Transform::from_xyz(position.x, position.y, -0.5),
+                ..default()
+            },
+            Obstacle { size },
+        ))
+        .id()
+}
+
+// The areas of every obstacle, used to resolve collisions
+pub fn obstacle_areas<'a>(obstacles: impl Iterator<Item = (&'a Transform, &'a Obstacle)>) -> Vec<Rect> {
+    obstacles
+        .map(|(transform, obstacle)| obstacle.area(transform.translation.truncate()))
+        .collect()
+}
+
+// Stop enemies from walking through obstacles or out of the map, runs after every behavior moved its enemies
+pub fn block_enemies(
+    bounds: Res<MapBounds>,
+    mut enemy_query: Query<(&mut Transform, &Enemy), (Without<Player>, Without<Obstacle>)>,
+    obstacle_query: Query<(&Transform, &Obstacle), Without<Enemy>>,
+) {
+    let obstacles = obstacle_areas(obstacle_query.iter());
+    if obstacles.is_empty() && bounds.0.is_none() {
+        return;
+    }
+    for (mut transform, enemy) in enemy_query.iter_mut() {
+        let position = resolve_collisions(transform.translation.truncate(), enemy.size, &obstacles, &bounds);
+        if position != transform.translation.truncate() {
+            transform.translation.x = position.x;
+            transform.translation.y = position.y;
+        }
+    }
+}
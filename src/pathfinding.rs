@@ -0,0 +1,278 @@
+// This is the pathfinding module, a flow field on a grid around the camera tells every enemy which way leads to the player
+// The field is computed once for all enemies, so it doesn't matter how big the horde is
+// It is only recomputed when the player moves to another cell or the obstacles change
+// When the player moves the new field is built a few hundred cells per frame, enemies follow the old one until it is done
+
+use crate::obstacle::Obstacle;
+use crate::player::Player;
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+// Size of a grid cell in world units
+pub const CELL_SIZE: f32 = 50.0;
+// How many cells the grid reaches out from its center in every direction
+pub const FLOW_FIELD_RADIUS: i32 = 30;
+// The grid is only moved once the camera is this many cells away from its center
+pub const RECENTER_DISTANCE: i32 = FLOW_FIELD_RADIUS / 4;
+// How many cells the search looks at every frame while the player moves
+pub const CELLS_PER_FRAME: usize = 800;
+
+const UNREACHABLE: u32 = u32::MAX;
+
+#[derive(Resource)]
+pub struct FlowField {
+    pub cell_size: f32,
+    pub radius: i32,
+    // The cell in the middle of the grid
+    pub center: IVec2,
+    // The cell the field leads to, None before the first update
+    pub target: Option<IVec2>,
+    pub blocked: Vec<bool>,
+    // Steps from every cell to the target
+    pub distances: Vec<u32>,
+    // Normalized direction to walk in from every cell, zero for the target and unreachable cells
+    pub directions: Vec<Vec2>,
+    // The search towards the player's new cell, while it runs the field still leads to the old target
+    pending: Option<Search>,
+}
+
+// A breadth first search that is spread over several frames
+struct Search {
+    target: IVec2,
+    distances: Vec<u32>,
+    queue: VecDeque<usize>,
+    directions: Vec<Vec2>,
+    // How many cells already have their direction, the directions are worked out after the search is done
+    directed: usize,
+}
+
+impl Default for FlowField {
+    fn default() -> Self {
+        FlowField::new(CELL_SIZE, FLOW_FIELD_RADIUS)
+    }
+}
+
+impl FlowField {
+    pub fn new(cell_size: f32, radius: i32) -> Self {
+        let cells = ((radius * 2 + 1) * (radius * 2 + 1)) as usize;
+        FlowField {
+            cell_size,
+            radius,
+            center: IVec2::ZERO,
+            target: None,
+            blocked: vec![false; cells],
+            distances: vec![UNREACHABLE; cells],
+            directions: vec![Vec2::ZERO; cells],
+            pending: None,
+        }
+    }
+
+    fn width(&self) -> i32 {
+        self.radius * 2 + 1
+    }
+
+    // The cell a world position falls in
+    pub fn cell(&self, position: Vec2) -> IVec2 {
+        (position / self.cell_size).floor().as_ivec2()
+    }
+
+    // The world position of the middle of a cell
+    pub fn cell_center(&self, cell: IVec2) -> Vec2 {
+        (cell.as_vec2() + 0.5) * self.cell_size
+    }
+
+    // Where a cell is stored, None when it's outside of the grid
+    fn index(&self, cell: IVec2) -> Option<usize> {
+        let local = cell - self.center + IVec2::splat(self.radius);
+        let width = self.width();
+        if local.x < 0 || local.y < 0 || local.x >= width || local.y >= width {
+            return None;
+        }
+        Some((local.y * width + local.x) as usize)
+    }
+
+    fn cell_at(&self, index: usize) -> IVec2 {
+        let width = self.width();
+        IVec2::new(index as i32 % width, index as i32 / width) + self.center - IVec2::splat(self.radius)
+    }
+
+    pub fn is_blocked(&self, cell: IVec2) -> bool {
+        self.index(cell).is_some_and(|index| self.blocked[index])
+    }
+
+    // Move the grid and mark every cell that overlaps one of the obstacles as blocked
+    pub fn set_obstacles(&mut self, center: IVec2, obstacles: &[Rect]) {
+        self.center = center;
+        self.blocked.fill(false);
+        for obstacle in obstacles {
+            let min = self.cell(obstacle.min);
+            // Obstacles ending exactly on a cell border don't block the next cell
+            let max = self.cell(obstacle.max - Vec2::splat(0.001));
+            for y in min.y..=max.y {
+                for x in min.x..=max.x {
+                    if let Some(index) = self.index(IVec2::new(x, y)) {
+                        self.blocked[index] = true;
+                    }
+                }
+            }
+        }
+    }
+
+    // Recompute the distances and directions towards the target position right away
+    pub fn compute(&mut self, target: Vec2) {
+        self.start(target);
+        self.step(usize::MAX);
+    }
+
+    // Start a search towards the target position, the field keeps its old directions until step finishes it
+    pub fn start(&mut self, target: Vec2) {
+        let target = self.cell(target);
+        let cells = self.distances.len();
+        let mut search = Search {
+            target,
+            distances: vec![UNREACHABLE; cells],
+            queue: VecDeque::new(),
+            directions: vec![Vec2::ZERO; cells],
+            directed: 0,
+        };
+        if let Some(start) = self.index(target) {
+            search.distances[start] = 0;
+            search.queue.push_back(start);
+        }
+        self.pending = Some(search);
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    // The cell the field will lead to once the running search is done
+    pub fn next_target(&self) -> Option<IVec2> {
+        self.pending.as_ref().map(|search| search.target).or(self.target)
+    }
+
+    // Work on the running search for up to budget cells, returns true once the new field is in use
+    pub fn step(&mut self, budget: usize) -> bool {
+        let Some(mut search) = self.pending.take() else {
+            return true;
+        };
+        let mut budget = budget;
+        let orthogonal = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y];
+        while budget > 0 {
+            let Some(index) = search.queue.pop_front() else {
+                break;
+            };
+            budget -= 1;
+            let cell = self.cell_at(index);
+            for offset in orthogonal {
+                let Some(next) = self.index(cell + offset) else {
+                    continue;
+                };
+                if self.blocked[next] || search.distances[next] != UNREACHABLE {
+                    continue;
+                }
+                search.distances[next] = search.distances[index] + 1;
+                search.queue.push_back(next);
+            }
+        }
+        if !search.queue.is_empty() {
+            self.pending = Some(search);
+            return false;
+        }
+        while budget > 0 && search.directed < search.distances.len() {
+            search.directions[search.directed] = self.best_direction(&search.distances, search.directed);
+            search.directed += 1;
+            budget -= 1;
+        }
+        if search.directed < search.distances.len() {
+            self.pending = Some(search);
+            return false;
+        }
+        self.target = Some(search.target);
+        self.distances = search.distances;
+        self.directions = search.directions;
+        true
+    }
+
+    // Every cell points at its closest neighbour, diagonals make the paths look less blocky
+    fn best_direction(&self, distances: &[u32], index: usize) -> Vec2 {
+        let distance = distances[index];
+        if distance == 0 || distance == UNREACHABLE {
+            return Vec2::ZERO;
+        }
+        let cell = self.cell_at(index);
+        let mut best = (distance, IVec2::ZERO);
+        for y in -1..=1 {
+            for x in -1..=1 {
+                let offset = IVec2::new(x, y);
+                if offset == IVec2::ZERO {
+                    continue;
+                }
+                // Don't cut corners past an obstacle
+                if x != 0 && y != 0
+                    && (self.is_blocked(cell + IVec2::new(x, 0)) || self.is_blocked(cell + IVec2::new(0, y)))
+                {
+                    continue;
+                }
+                let Some(next) = self.index(cell + offset) else {
+                    continue;
+                };
+                if distances[next] < best.0 {
+                    best = (distances[next], offset);
+                }
+            }
+        }
+        best.1.as_vec2().normalize_or_zero()
+    }
+
+    // The direction to walk in from a world position, None when the position is outside of the grid,
+    // in the target cell, or can't reach the target at all, then walking straight at the player is the best guess
+    pub fn direction(&self, position: Vec2) -> Option<Vec2> {
+        let index = self.index(self.cell(position))?;
+        let direction = self.directions[index];
+        if direction == Vec2::ZERO {
+            return None;
+        }
+        Some(direction)
+    }
+}
+
+// Keep the flow field centered on the camera and pointing at the player, only doing work when something changed
+pub fn update_flow_field(
+    mut flow_field: ResMut<FlowField>,
+    player_query: Query<&Transform, With<Player>>,
+    camera_query: Query<&Transform, With<crate::camera::Camera>>,
+    obstacle_query: Query<(&Transform, &Obstacle)>,
+    changed_obstacles: Query<(), (With<Obstacle>, Or<(Changed<Transform>, Changed<Obstacle>)>)>,
+    mut removed_obstacles: RemovedComponents<Obstacle>,
+) {
+    let (Ok(player_transform), Ok(camera_transform)) = (player_query.get_single(), camera_query.get_single()) else {
+        return;
+    };
+    let camera_cell = flow_field.cell(camera_transform.translation.truncate());
+    let offset = (camera_cell - flow_field.center).abs();
+    let recenter = flow_field.target.is_none() || offset.max_element() > RECENTER_DISTANCE;
+    // Obstacles rarely move, so the blocked cells are only rebuilt when one was added, moved or removed
+    // The removals are always read, otherwise they would trigger another rebuild on the next frame
+    let removed = removed_obstacles.read().count() > 0;
+    let changed = !changed_obstacles.is_empty();
+    let obstacles_changed = changed || removed;
+    if recenter || obstacles_changed {
+        let center = if recenter { camera_cell } else { flow_field.center };
+        let obstacles: Vec<Rect> = obstacle_query
+            .iter()
+            .map(|(transform, obstacle)| obstacle.area(transform.translation.truncate()))
+            .collect();
+        flow_field.set_obstacles(center, &obstacles);
+    }
+    let player_position = player_transform.translation.truncate();
+    // Moving the grid or its obstacles makes the old field useless, so that is recomputed at once
+    if recenter || obstacles_changed {
+        flow_field.compute(player_position);
+        return;
+    }
+    if flow_field.next_target() != Some(flow_field.cell(player_position)) {
+        flow_field.start(player_position);
+    }
+    flow_field.step(CELLS_PER_FRAME);
+}
@@ -0,0 +1,142 @@
+// This is the pickup module, pickups are items on the map that do something special when the player walks over them
+
+use crate::camera;
+use crate::damage::{DamageEvent, DamageKind};
+use crate::enemy::{Boss, Enemy};
+use crate::experience_item::ExperienceItem;
+use crate::game_state::GameState;
+use crate::player::Player;
+use crate::status::{StatusEffect, StatusEvent, StatusKind};
+use bevy::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PickupKind {
+    // Pulls every experience item on the map towards the player
+    Magnet,
+    // A coin worth this much gold
+    Gold(u32),
+    // Pauses the game and rolls rewards
+    Chest,
+    // Floor food that heals this much health
+    Food(i32),
+    // Kills every enemy on screen except bosses
+    Bomb,
+    // Freezes every enemy for a few seconds
+    Clock,
+    // Makes the player invulnerable for a few seconds
+    Cross,
+}
+
+// How long the clock and cross pickups last
+pub const CLOCK_FREEZE_DURATION: f32 = 5.0;
+pub const CROSS_INVULNERABLE_DURATION: f32 = 10.0;
+
+impl PickupKind {
+    // Pickups don't have their own sprites yet, so they are drawn as colored squares
+    pub fn color(&self) -> Color {
+        match self {
+            PickupKind::Magnet => Color::rgb(0.9, 0.1, 0.1),
+            PickupKind::Gold(_) => Color::rgb(1.0, 0.85, 0.1),
+            PickupKind::Chest => Color::rgb(0.6, 0.35, 0.1),
+            PickupKind::Food(_) => Color::rgb(0.2, 0.9, 0.3),
+            PickupKind::Bomb => Color::rgb(0.2, 0.2, 0.2),
+            PickupKind::Clock => Color::rgb(0.4, 0.8, 1.0),
+            PickupKind::Cross => Color::rgb(1.0, 1.0, 0.8),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Pickup {
+    pub kind: PickupKind,
+    pub size: Vec2,
+}
+
+pub fn spawn_pickup(commands: &mut Commands, kind: PickupKind, position: Vec2) {
+    let size = Vec2::new(20.0, 20.0);
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: kind.color(),
+                custom_size: Some(size),
+                ..default()
+            },
+            transform: Transform::from_xyz(position.x, position.y, 0.0),
+            ..default()
+        },
+        Pickup { kind, size },
+    ));
+}
+
+// Function checks collision with pickups and the player, if the player collides with a pickup its effect is applied and the pickup is despawned
+pub fn pickup_collision(
+    mut commands: Commands,
+    mut player_query: Query<(Entity, &mut Player, &Transform)>,
+    pickup_query: Query<(Entity, &Transform, &Pickup)>,
+    mut experience_query: Query<&mut ExperienceItem>,
+    enemy_query: Query<(Entity, &Transform, &Enemy), Without<Boss>>,
+    camera_query: Query<(&OrthographicProjection, &Transform), With<camera::Camera>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut status_events: EventWriter<StatusEvent>,
+) {
+    let Ok((player_entity, mut player, player_transform)) = player_query.get_single_mut() else {
+        return;
+    };
+    for (entity, pickup_transform, pickup) in pickup_query.iter() {
+        let offset = (player_transform.translation - pickup_transform.translation).truncate().abs();
+        if offset.x > (player.size.x + pickup.size.x) / 2.0 || offset.y > (player.size.y + pickup.size.y) / 2.0 {
+            continue;
+        }
+        match pickup.kind {
+            PickupKind::Magnet => {
+                for mut experience_item in experience_query.iter_mut() {
+                    experience_item.attracted = true;
+                }
+            }
+            PickupKind::Gold(amount) => player.gold += amount,
+            PickupKind::Chest => next_state.set(GameState::ChestOpening),
+            PickupKind::Food(amount) => player.heal(amount),
+            PickupKind::Bomb => {
+                let Ok((projection, camera_transform)) = camera_query.get_single() else {
+                    continue;
+                };
+                let screen = camera::visible_area(projection, camera_transform);
+                for (enemy_entity, enemy_transform, enemy) in enemy_query.iter() {
+                    if screen.contains(enemy_transform.translation.truncate()) {
+                        damage_events.send(DamageEvent {
+                            target: enemy_entity,
+                            amount: enemy.health,
+                            kind: DamageKind::Fire,
+                            critical: false,
+                            lethal: true,
+                        });
+                    }
+                }
+            }
+            PickupKind::Clock => {
+                for (enemy_entity, _, _) in enemy_query.iter() {
+                    status_events.send(StatusEvent {
+                        target: enemy_entity,
+                        effect: StatusEffect {
+                            kind: StatusKind::Freeze,
+                            duration: CLOCK_FREEZE_DURATION,
+                            potency: 1.0,
+                        },
+                    });
+                }
+            }
+            PickupKind::Cross => {
+                status_events.send(StatusEvent {
+                    target: player_entity,
+                    effect: StatusEffect {
+                        kind: StatusKind::Invulnerable,
+                        duration: CROSS_INVULNERABLE_DURATION,
+                        potency: 1.0,
+                    },
+                });
+            }
+        }
+        commands.entity(entity).despawn();
+    }
+}
@@ -1,5 +1,7 @@
 // This is a bevy app and this is the player module, it handles moving the player with the keyboard and rendering the player
 
+use crate::obstacle::{self, MapBounds, Obstacle};
+use crate::status::{StatusEffects, StatusKind};
 use bevy::prelude::*;
 #[derive(Component)]
 pub struct Player {
@@ -12,6 +14,51 @@ pub struct Player {
     pub level: i32,
     pub experience_to_next_level: i32,
     pub recently_hit: bool,
+    // The direction the player last moved in, used by weapons that fire where the player is facing
+    pub facing: Vec2,
+    // Experience items closer than this fly towards the player
+    pub magnet_radius: f32,
+    // Multiplies the chance of rare drops
+    pub luck: f32,
+    // Gold collected this run
+    pub gold: u32,
+    // Healing can never bring health above this
+    pub max_health: i32,
+    // Health regained per second
+    pub regen: f32,
+    // Regen that has built up but not yet added up to a whole point of health
+    pub regen_progress: f32,
+}
+
+// A fresh player at the center of the map, without any upgrades
+impl Default for Player {
+    fn default() -> Self {
+        Player {
+            health: 100,
+            position: (0., 0.),
+            move_speed: 100.,
+            fire_rate: 4.,
+            size: Vec2::new(50., 50.),
+            experience: 0,
+            level: 1,
+            experience_to_next_level: 10,
+            recently_hit: false,
+            facing: Vec2::X,
+            magnet_radius: 100.,
+            luck: 1.,
+            gold: 0,
+            max_health: 100,
+            regen: 0.,
+            regen_progress: 0.,
+        }
+    }
+}
+
+impl Player {
+    // Heal the player without going over max health
+    pub fn heal(&mut self, amount: i32) {
+        self.health = (self.health + amount).min(self.max_health);
+    }
 }
 
 #[derive(Component)]
@@ -23,8 +70,11 @@ pub struct ProjectileTimer(pub Timer);
 // Function for moving the player based on wasd keyboard input, include delta time for smooth movement
 pub fn sprite_movement(time: Res<Time>,
     keyboard_input: Res<Input<KeyCode>>,
-    mut player_query: Query<(&mut Transform, &mut Player), Without<crate::enemy::Enemy>>,
+    bounds: Res<MapBounds>,
+    mut player_query: Query<(&mut Transform, &mut Player), (Without<crate::enemy::Enemy>, Without<Obstacle>)>,
+    obstacle_query: Query<(&Transform, &Obstacle), Without<Player>>,
 ) {
+    let obstacles = obstacle::obstacle_areas(obstacle_query.iter());
     for (mut transform, mut player) in player_query.iter_mut() {
         let mut x = transform.translation.x;
         let mut y = transform.translation.y;
@@ -40,21 +90,32 @@ pub fn sprite_movement(time: Res<Time>,
         if keyboard_input.pressed(KeyCode::D) {
             x += player.move_speed * time.delta_seconds();
         }
-        transform.translation.x = x;
-        transform.translation.y = y;
-        player.position = (x, y);
+        let moved = Vec2::new(x - transform.translation.x, y - transform.translation.y);
+        let facing = moved.try_normalize().unwrap_or(player.facing);
+        // Walls, trees and rocks push the player back out, and the player can't leave the map
+        let position = obstacle::resolve_collisions(Vec2::new(x, y), player.size, &obstacles, &bounds);
+        // The transform is only written when the player moved, so Changed<Transform> means the player really moved
+        if transform.translation.truncate() != position {
+            transform.translation.x = position.x;
+            transform.translation.y = position.y;
+        }
+        // Only touch the player when it really moved, so Changed<Player> doesn't fire every frame
+        if player.position != (position.x, position.y) || player.facing != facing {
+            player.position = (position.x, position.y);
+            player.facing = facing;
+        }
     }
 }
 
 // Function to check collision with enemies and the player, if the player is hit, we decrease his health
 pub fn player_collision(
-    mut commands: Commands,
-    mut player_query: Query<(Entity, &Transform, &Player), Without<crate::enemy::Enemy>>,
+    mut player_query: Query<(&Transform, &mut Player, &StatusEffects), Without<crate::enemy::Enemy>>,
     mut enemy_query: Query<(&Transform, &crate::enemy::Enemy), Without<Player>>,
-    mut health_text_query: Query<&mut Text, With<crate::ui::HealthText>>
 ) {
-    for (entity, player_transform, player) in player_query.iter_mut() {
-        for (enemy_transform, _) in enemy_query.iter_mut() {
+    for (player_transform, mut player, statuses) in player_query.iter_mut() {
+        // An invulnerable player can't be hit at all, except by the reaper
+        let invulnerable = statuses.has(StatusKind::Invulnerable);
+        for (enemy_transform, enemy) in enemy_query.iter_mut() {
             let player_x = player_transform.translation.x;
             let player_y = player_transform.translation.y;
             let enemy_x = enemy_transform.translation.x;
@@ -64,44 +125,37 @@ pub fn player_collision(
                 && player_y - player.size.y / 2.0 < enemy_y + 25.0
                 && player_y + player.size.y / 2.0 > enemy_y - 25.0
             {
-                // Check if player was recetently hit
-                if player.recently_hit {
+                // The reaper comes once the run is won, a single touch takes all of the player's health
+                if enemy.kind.unbeatable() {
+                    if player.health > 0 {
+                        player.health = 0;
+                        player.recently_hit = true;
+                    }
                     continue;
                 }
-                println!("Player hit!");
-                // Update health text ui component
-                for mut health_text in health_text_query.iter_mut() {
-                    // Ui text component
-                    health_text.sections[0].value = format!("Health: {}", player.health - 1);
-                    
+                // Check if player was recetently hit
+                if invulnerable || player.recently_hit {
+                    continue;
                 }
-
-                // Decrease player health
-                commands.entity(entity).insert(Player {
-                    health: player.health - 1,
-                    recently_hit: true,
-                    ..*player
-                });
-            }else{
+                // Decrease player health, the player is changed in place so nothing else done to it this frame is lost
+                player.health -= 1;
+                player.recently_hit = true;
             }
         }
     }
 }
 
 pub fn update_hit_timer(
-    mut commands: Commands,
     time: Res<Time>, 
-    mut player_query: Query<(Entity, &mut Player)>,
+    mut player_query: Query<&mut Player>,
     mut player_hit_timer_query: Query<&mut PlayerHitTimer>,
 )    {
-    for (entity, mut player) in player_query.iter_mut() {
+    for mut player in player_query.iter_mut() {
         for mut timer in player_hit_timer_query.iter_mut() {
             timer.0.tick(time.delta());
-            if timer.0.finished() {
-                commands.entity(entity).insert(Player {
-                    recently_hit: false,
-                    ..*player
-                });
+            // Only touch the player when the flag actually changes, so Changed<Player> means something changed
+            if timer.0.finished() && player.recently_hit {
+                player.recently_hit = false;
             }
         }
     }
@@ -112,8 +166,6 @@ pub fn experience_collision(
     mut commands: Commands,
     mut player_query: Query<&mut Player>,
     mut experience_query: Query<(Entity, &Transform, &crate::experience_item::ExperienceItem)>,
-    mut experience_text_query: Query<&mut Text, (With<crate::ui::ExperienceText>, Without<crate::ui::LevelText>)>,
-    mut level_text_query: Query<&mut Text, (With<crate::ui::LevelText>, Without<crate::ui::ExperienceText>)>,
 ) {
     for mut player in player_query.iter_mut() {
         for (entity, experience_transform, experience_item) in experience_query.iter_mut() {
@@ -132,17 +184,7 @@ pub fn experience_collision(
                 if player.experience >= player.experience_to_next_level {
                     player.level += 1;
                     player.experience = 0;
-                    player.experience_to_next_level = player.experience_to_next_level * 2;
-                }
-                // Update experience text ui component
-                for mut experience_text in experience_text_query.iter_mut() {
-                    // Ui text component
-                    experience_text.sections[0].value = format!("Experience: {}", player.experience);
-                }
-                // Update the player level text ui component
-                for mut level_text in level_text_query.iter_mut() {
-                    // Ui text component
-                    level_text.sections[0].value = format!("Level: {}", player.level);
+                    player.experience_to_next_level *= 2;
                 }
                 // Despawn experience item
                 commands.entity(entity).despawn();
@@ -151,3 +193,25 @@ pub fn experience_collision(
     }
     
 }
+
+// Slowly heal the player based on their regen stat, only whole points of health are added
+pub fn regenerate_health(
+    time: Res<Time>,
+    mut player_query: Query<&mut Player>,
+) {
+    for mut player in player_query.iter_mut() {
+        // The progress is only bookkeeping, the player counts as changed once health is actually added
+        let stats = player.bypass_change_detection();
+        if stats.health >= stats.max_health {
+            stats.regen_progress = 0.0;
+            continue;
+        }
+        stats.regen_progress += stats.regen * time.delta_seconds();
+        if stats.regen_progress < 1.0 {
+            continue;
+        }
+        let amount = stats.regen_progress.floor();
+        stats.regen_progress -= amount;
+        player.heal(amount as i32);
+    }
+}
@@ -0,0 +1,100 @@
+// This is the progress module, it remembers how far the player got in previous runs so stages can be unlocked
+// Progress is saved to save/progress.ron whenever a record is beaten and when the game closes
+
+use crate::damage::EnemyDeathEvent;
+use crate::player::Player;
+use crate::stage::{RunClock, SelectedStage};
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub const PROGRESS_PATH: &str = "save/progress.ron";
+
+// The best results on a single stage
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StageRecord {
+    pub best_level: i32,
+    // Whole minutes survived in the longest run
+    pub best_minutes: u32,
+    // The run was survived until the end
+    pub cleared: bool,
+}
+
+#[derive(Resource, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Progress {
+    // Records by stage name
+    #[serde(default)]
+    pub stages: HashMap<String, StageRecord>,
+    // Enemies killed over all runs
+    #[serde(default)]
+    pub total_kills: u32,
+}
+
+impl Progress {
+    pub fn record(&self, stage: &str) -> StageRecord {
+        self.stages.get(stage).cloned().unwrap_or_default()
+    }
+
+    pub fn from_ron(source: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(source)
+    }
+
+    pub fn to_ron(&self) -> String {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).expect("Progress can always be serialized")
+    }
+
+    pub fn save(&self) {
+        let result = std::fs::create_dir_all("save").and_then(|_| std::fs::write(PROGRESS_PATH, self.to_ron()));
+        if let Err(error) = result {
+            warn!("Could not save progress to {}: {}", PROGRESS_PATH, error);
+        }
+    }
+}
+
+// Read the saved progress, a missing or broken save starts from scratch
+pub fn load_progress(mut commands: Commands) {
+    let progress = std::fs::read_to_string(PROGRESS_PATH)
+        .ok()
+        .and_then(|source| match Progress::from_ron(&source) {
+            Ok(progress) => Some(progress),
+            Err(error) => {
+                warn!("Could not parse {}: {}", PROGRESS_PATH, error);
+                None
+            }
+        })
+        .unwrap_or_default();
+    commands.insert_resource(progress);
+}
+
+// Keep the records of the current stage up to date, saving whenever one of them is beaten
+pub fn record_progress(
+    mut progress: ResMut<Progress>,
+    stage: Res<SelectedStage>,
+    clock: Res<RunClock>,
+    mut death_events: EventReader<EnemyDeathEvent>,
+    player_query: Query<&Player>,
+) {
+    let kills = death_events.read().count() as u32;
+    if kills > 0 {
+        progress.total_kills += kills;
+    }
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    let minutes = (clock.elapsed / 60.0) as u32;
+    let record = progress.record(&stage.0.name);
+    if player.level > record.best_level || minutes > record.best_minutes {
+        let record = progress.stages.entry(stage.0.name.clone()).or_default();
+        record.best_level = record.best_level.max(player.level);
+        record.best_minutes = record.best_minutes.max(minutes);
+        progress.save();
+    }
+}
+
+// Kills don't trigger a save on their own, so save one last time when the game closes
+pub fn save_progress_on_exit(mut exit_events: EventReader<AppExit>, progress: Res<Progress>) {
+    if exit_events.read().next().is_some() {
+        progress.save();
+    }
+}
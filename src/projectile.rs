@@ -1,81 +1,276 @@
+use crate::aim;
+use crate::camera;
 use crate::enemy;
 use crate::player;
+use crate::settings;
+use crate::status::StatusEffect;
+use crate::weapon;
 use bevy::prelude::*;
+use rand::Rng;
+use std::f32::consts::TAU;
+
+// How a projectile moves after it has been fired, each weapon picks one of these
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProjectileMotion {
+    // Flies in a straight line at a constant speed
+    Straight,
+    // Slows down until it stops, then speeds back up towards the player
+    Boomerang { deceleration: f32 },
+    // Turns towards the closest enemy by at most turn_rate radians per second
+    Homing { turn_rate: f32 },
+    // Wiggles sideways around the line it was fired along
+    SineWave { amplitude: f32, frequency: f32 },
+    // Circles around where it was fired while moving outwards
+    Spiral { angular_speed: f32 },
+    // Falls downwards like a thrown object
+    Gravity { gravity: f32 },
+}
 
 #[derive(Component)]
 pub struct Projectile {
     pub x_speed: f32,
     pub y_speed: f32,
     pub size: Vec2,
+    pub motion: ProjectileMotion,
+    // Where the projectile was fired from
+    pub origin: Vec2,
+    // How many seconds the projectile has been alive
+    pub age: f32,
+    pub lifetime: f32,
+    // Set once a boomerang has stopped and is flying back to the player
+    pub returning: bool,
+    // Status applied to the enemies this projectile hits
+    pub on_hit: Option<StatusEffect>,
+    pub crit_chance: f32,
+    // Set when the projectile hit something, it is despawned at the end of the frame and can't hit anything else
+    pub spent: bool,
+}
+
+impl Projectile {
+    pub fn new(origin: Vec2, velocity: Vec2, motion: ProjectileMotion, lifetime: f32) -> Self {
+        Projectile {
+            x_speed: velocity.x,
+            y_speed: velocity.y,
+            size: Vec2::new(5.0, 5.0),
+            motion,
+            origin,
+            age: 0.0,
+            lifetime,
+            returning: false,
+            on_hit: None,
+            crit_chance: 0.0,
+            spent: false,
+        }
+    }
+
+    pub fn velocity(&self) -> Vec2 {
+        Vec2::new(self.x_speed, self.y_speed)
+    }
+
+    // Roll how much damage a hit does and whether it was critical, critical hits do double damage
+    pub fn roll_damage(&self, rng: &mut impl Rng) -> (i32, bool) {
+        let critical = rng.gen_bool(self.crit_chance.clamp(0.0, 1.0) as f64);
+        (if critical { 2 } else { 1 }, critical)
+    }
+
+    fn set_velocity(&mut self, velocity: Vec2) {
+        self.x_speed = velocity.x;
+        self.y_speed = velocity.y;
+    }
+
+    // Move the projectile forward by delta seconds and return its new position
+    // The player position is needed for boomerangs, the target for homing projectiles
+    pub fn advance(&mut self, position: Vec2, delta: f32, player: Vec2, target: Option<Vec2>) -> Vec2 {
+        self.age += delta;
+        match self.motion {
+            ProjectileMotion::Straight => position + self.velocity() * delta,
+            ProjectileMotion::Boomerang { deceleration } => {
+                let speed = self.velocity().length();
+                if !self.returning {
+                    let speed = speed - deceleration * delta;
+                    if speed <= 0.0 {
+                        // It has stopped, from now on it flies back to the player
+                        self.returning = true;
+                        self.set_velocity(Vec2::ZERO);
+                    } else {
+                        self.set_velocity(self.velocity().normalize() * speed);
+                    }
+                } else {
+                    let direction = (player - position).normalize_or_zero();
+                    self.set_velocity(direction * (speed + deceleration * delta));
+                }
+                position + self.velocity() * delta
+            }
+            ProjectileMotion::Homing { turn_rate } => {
+                if let Some(target) = target {
+                    let velocity = self.velocity();
+                    let angle = velocity.angle_between(target - position);
+                    if angle.is_finite() {
+                        let max_turn = turn_rate * delta;
+                        let turn = angle.clamp(-max_turn, max_turn);
+                        self.set_velocity(Vec2::from_angle(turn).rotate(velocity));
+                    }
+                }
+                position + self.velocity() * delta
+            }
+            ProjectileMotion::SineWave { amplitude, frequency } => {
+                // x_speed and y_speed stay the firing velocity, the wave is added on top of that line
+                let velocity = self.velocity();
+                let sideways = velocity.normalize_or_zero().perp();
+                self.origin
+                    + velocity * self.age
+                    + sideways * amplitude * (self.age * frequency * TAU).sin()
+            }
+            ProjectileMotion::Spiral { angular_speed } => {
+                // The distance from the origin grows with the firing speed while the angle keeps turning
+                let velocity = self.velocity();
+                let radius = velocity.length() * self.age;
+                let angle = velocity.y.atan2(velocity.x) + angular_speed * self.age;
+                self.origin + Vec2::from_angle(angle) * radius
+            }
+            ProjectileMotion::Gravity { gravity } => {
+                self.y_speed -= gravity * delta;
+                position + self.velocity() * delta
+            }
+        }
+    }
+
+    // A projectile is done once its lifetime is over, or once a boomerang made it back to the player
+    pub fn is_finished(&self, position: Vec2, player: Vec2) -> bool {
+        self.age >= self.lifetime || (self.returning && position.distance(player) < 20.0)
+    }
 }
 
 #[derive(Component)]
 pub struct ProjectileTimer(pub Timer);
 
-// Fire a projectile from the player's position, assuming the timer is finished
+// Queue a volley of shots for every weapon whose timer is finished
 pub fn fire_projectile(
     time: Res<Time>,
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    player_query: Query<
-        (&player::Player, &Transform),
-        (With<player::Player>, Without<enemy::Enemy>),
-    >,
-    // Query projectile timer
-    mut projectile_timer_query: Query<&mut ProjectileTimer>,
-    enemy_query: Query<(&Transform, &enemy::Enemy), Without<player::Player>>,
+    // Query projectile timers, every weapon has its own timer
+    mut projectile_timer_query: Query<(Entity, &mut ProjectileTimer, &weapon::Weapon)>,
 ) {
-    // If the projectile timer is finished, fire a projectile
-    for mut timer in projectile_timer_query.iter_mut() {
+    for (weapon_entity, mut timer, weapon) in projectile_timer_query.iter_mut() {
         if timer.0.tick(time.delta()).just_finished() {
-            for (_, player_transform) in player_query.iter() {
-                // Find the closest enemy to the player, than fire a projectile at it
-                let mut closest_enemy = None;
-                let mut closest_distance = f32::MAX;
-                for (enemy_transform, _) in enemy_query.iter() {
-                    let x_diff = enemy_transform.translation.x - player_transform.translation.x;
-                    let y_diff = enemy_transform.translation.y - player_transform.translation.y;
-                    let distance = (x_diff.powi(2) + y_diff.powi(2)).sqrt();
-                    if distance < closest_distance {
-                        closest_distance = distance;
-                        closest_enemy = Some((x_diff, y_diff));
-                    }
-                }
-                if let Some((x_diff, y_diff)) = closest_enemy {
-                    let distance = (x_diff.powi(2) + y_diff.powi(2)).sqrt();
-                    let x_speed = x_diff / distance * 200.0;
-                    let y_speed = y_diff / distance * 200.0;
-                    commands.spawn((
-                        SpriteBundle {
-                            texture: asset_server.load("branding/projectile.png"),
-                            transform: Transform {
-                                translation: player_transform.translation,
-                                rotation: Quat::IDENTITY,
-                                scale: Vec3::new(0.1, 0.1, 0.1),
-                            },
-                            ..Default::default()
-                        },
-                        Projectile {
-                            x_speed,
-                            y_speed,
-                            size: Vec2::new(5.0, 5.0),
-                        },
-                    ));
-                }
-
-                
+            // Every projectile of the volley waits a little longer than the one before it
+            for index in 0..weapon.amount {
+                commands.spawn(weapon::QueuedShot {
+                    weapon: weapon_entity,
+                    index,
+                    delay: Timer::from_seconds(
+                        weapon.volley_delay * index as f32,
+                        TimerMode::Once,
+                    ),
+                });
             }
         }
     }
 }
 
+// Fire the queued shots whose delay is over, each shot picks its own target when it is fired
+pub fn fire_queued_shots(
+    time: Res<Time>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    cursor: Res<camera::CursorPosition>,
+    settings: Res<settings::Settings>,
+    aim: Res<aim::AimDirection>,
+    mut shot_query: Query<(Entity, &mut weapon::QueuedShot)>,
+    weapon_query: Query<&weapon::Weapon>,
+    player_query: Query<(&player::Player, &Transform), Without<enemy::Enemy>>,
+    enemy_query: Query<(&Transform, &enemy::Enemy), Without<player::Player>>,
+) {
+    let Ok((player, player_transform)) = player_query.get_single() else {
+        return;
+    };
+    let player_position = player_transform.translation.truncate();
+    let enemies: Vec<(Vec2, i32)> = enemy_query
+        .iter()
+        .map(|(enemy_transform, enemy)| (enemy_transform.translation.truncate(), enemy.health))
+        .collect();
+    for (shot_entity, mut shot) in shot_query.iter_mut() {
+        if !shot.delay.tick(time.delta()).finished() {
+            continue;
+        }
+        commands.entity(shot_entity).despawn();
+        // The weapon may have been removed while the shot was waiting
+        let Ok(weapon) = weapon_query.get(shot.weapon) else {
+            continue;
+        };
+        // Manually aimed weapons fire where the player aims, falling back to auto-aim without aim input
+        let manual_target = match (settings.manual_aim && weapon.manual_aim, aim.0) {
+            (true, Some(direction)) => Some(player_position + direction),
+            _ => None,
+        };
+        let Some(target) = manual_target.or_else(|| {
+            weapon::pick_target(
+                weapon.targeting,
+                player_position,
+                player.facing,
+                cursor.0,
+                &enemies,
+                &mut rand::thread_rng(),
+            )
+        }) else {
+            continue;
+        };
+        let Some(direction) = (target - player_position).try_normalize() else {
+            continue;
+        };
+        let angle = weapon::spread_angle(shot.index, weapon.amount, weapon.spread);
+        let velocity = Vec2::from_angle(angle).rotate(direction) * weapon.projectile_speed;
+        commands.spawn((
+            SpriteBundle {
+                texture: asset_server.load(&weapon.texture),
+                transform: Transform {
+                    translation: player_transform.translation,
+                    rotation: Quat::IDENTITY,
+                    scale: Vec3::new(0.1, 0.1, 0.1),
+                },
+                ..Default::default()
+            },
+            Projectile {
+                on_hit: weapon.on_hit,
+                crit_chance: weapon.crit_chance,
+                ..Projectile::new(
+                    player_position,
+                    velocity,
+                    weapon.motion,
+                    weapon.projectile_lifetime,
+                )
+            },
+        ));
+    }
+}
 
+// Move every projectile according to its motion, and despawn the ones that are finished
 pub fn update_projectiles(
     time: Res<Time>,
+    mut commands: Commands,
     mut query: Query<(Entity, &mut Transform, &mut Projectile)>,
+    player_query: Query<&Transform, (With<player::Player>, Without<Projectile>)>,
+    enemy_query: Query<&Transform, (With<enemy::Enemy>, Without<Projectile>)>,
 ) {
-    for (_, mut transform, projectile) in query.iter_mut() {
-        transform.translation.x += projectile.x_speed * time.delta_seconds();
-        transform.translation.y += projectile.y_speed * time.delta_seconds();
+    let player_position = match player_query.get_single() {
+        Ok(player_transform) => player_transform.translation.truncate(),
+        Err(_) => return,
+    };
+    for (entity, mut transform, mut projectile) in query.iter_mut() {
+        let position = transform.translation.truncate();
+        // Homing projectiles steer towards the enemy closest to them
+        let target = match projectile.motion {
+            ProjectileMotion::Homing { .. } => enemy_query
+                .iter()
+                .map(|enemy_transform| enemy_transform.translation.truncate())
+                .min_by(|a, b| a.distance(position).total_cmp(&b.distance(position))),
+            _ => None,
+        };
+        let new_position = projectile.advance(position, time.delta_seconds(), player_position, target);
+        transform.translation.x = new_position.x;
+        transform.translation.y = new_position.y;
+        if projectile.is_finished(new_position, player_position) {
+            commands.entity(entity).despawn();
+        }
     }
-}
\ No newline at end of file
+}
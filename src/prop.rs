@@ -0,0 +1,115 @@
+// This is the prop module, props are decorations placed by the map
+// Braziers and crates can be broken by the player's weapons, then they drop items from their loot table
+
+use crate::damage::{DamageEvent, DamageKind};
+use crate::projectile::Projectile;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum PropKind {
+    Brazier,
+    Crate,
+    Statue,
+    Candle,
+}
+
+impl PropKind {
+    pub fn size(&self) -> Vec2 {
+        match self {
+            PropKind::Brazier => Vec2::new(30.0, 40.0),
+            PropKind::Crate => Vec2::new(35.0, 35.0),
+            PropKind::Statue => Vec2::new(40.0, 60.0),
+            PropKind::Candle => Vec2::new(10.0, 20.0),
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            PropKind::Brazier => Color::rgb(0.9, 0.45, 0.1),
+            PropKind::Crate => Color::rgb(0.55, 0.4, 0.2),
+            PropKind::Statue => Color::rgb(0.6, 0.6, 0.65),
+            PropKind::Candle => Color::rgb(1.0, 0.95, 0.8),
+        }
+    }
+
+    // How many hits the prop takes to break, None for props that can't be broken
+    pub fn health(&self) -> Option<i32> {
+        match self {
+            PropKind::Brazier => Some(1),
+            PropKind::Crate => Some(3),
+            PropKind::Statue | PropKind::Candle => None,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Prop {
+    pub kind: PropKind,
+    // Size of the prop's hitbox, the size it was placed with on the map
+    pub size: Vec2,
+}
+
+// Props with this component can be broken
+#[derive(Component)]
+pub struct Destructible {
+    pub health: i32,
+}
+
+// Sent when a prop breaks, the prop entity is already despawned
+#[derive(Event)]
+pub struct PropDestroyedEvent {
+    pub position: Vec2,
+    pub kind: PropKind,
+}
+
+pub fn spawn_prop(commands: &mut Commands, kind: PropKind, position: Vec2, size: Vec2) -> Entity {
+    let mut prop = commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: kind.color(),
+                custom_size: Some(size),
+                ..default()
+            },
+            transform: Transform::from_xyz(position.x, position.y, -0.5),
+            ..default()
+        },
+        Prop { kind, size },
+    ));
+    if let Some(health) = kind.health() {
+        prop.insert(Destructible { health });
+    }
+    prop.id()
+}
+
+// Projectiles break props the same way they hurt enemies, by sending a DamageEvent
+// Projectiles that already hit an enemy this frame are spent and pass through
+pub fn prop_collision(
+    mut commands: Commands,
+    prop_query: Query<(Entity, &Transform, &Prop), With<Destructible>>,
+    mut projectile_query: Query<(Entity, &Transform, &mut Projectile)>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for (projectile_entity, projectile_transform, mut projectile) in projectile_query.iter_mut() {
+        if projectile.spent {
+            continue;
+        }
+        let projectile_position = projectile_transform.translation.truncate();
+        for (prop_entity, prop_transform, prop) in prop_query.iter() {
+            let area = Rect::from_center_size(prop_transform.translation.truncate(), prop.size + Vec2::splat(10.0));
+            if area.contains(projectile_position) {
+                projectile.spent = true;
+                commands.entity(projectile_entity).despawn();
+                damage_events.send(DamageEvent {
+                    target: prop_entity,
+                    amount: 1,
+                    kind: DamageKind::Physical,
+                    critical: false,
+                    lethal: false,
+                });
+                // A projectile can only hit one prop
+                break;
+            }
+        }
+    }
+}
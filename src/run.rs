@@ -0,0 +1,111 @@
+// This is the run module, it keeps the stats of the current run and ends the run once the stage's run length is reached
+// Surviving until the end wins the run, then the reaper comes for the player and can't be stopped
+
+use crate::camera::{self, Camera};
+use crate::damage::EnemyDeathEvent;
+use crate::enemy::{self, Boss, EnemyKind};
+use crate::game_state::GameState;
+use crate::player::Player;
+use crate::progress::Progress;
+use crate::spawning::{self, SPAWN_MARGIN};
+use crate::stage::{RunClock, SelectedStage};
+use bevy::prelude::*;
+
+#[derive(Resource, Default)]
+pub struct RunStats {
+    pub kills: u32,
+    // The player survived until the end of the run
+    pub victory: bool,
+}
+
+// Format seconds as minutes and seconds, the way the run timer shows them
+pub fn format_time(seconds: f32) -> String {
+    let seconds = seconds.max(0.0) as u32;
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+pub fn count_kills(mut stats: ResMut<RunStats>, mut death_events: EventReader<EnemyDeathEvent>) {
+    let kills = death_events.read().count() as u32;
+    if kills > 0 {
+        stats.kills += kills;
+    }
+}
+
+// When the run length is reached the run is won, the stage is marked as cleared and the reaper spawns
+pub fn end_run(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    clock: Res<RunClock>,
+    stage: Res<SelectedStage>,
+    mut stats: ResMut<RunStats>,
+    mut progress: ResMut<Progress>,
+    camera_query: Query<(&OrthographicProjection, &Transform), With<Camera>>,
+) {
+    if stats.victory || clock.elapsed < stage.0.run_length {
+        return;
+    }
+    // Without a camera there is nowhere to spawn the reaper, so the run ends once there is one
+    let Ok((projection, camera_transform)) = camera_query.get_single() else {
+        return;
+    };
+    stats.victory = true;
+    progress.stages.entry(stage.0.name.clone()).or_default().cleared = true;
+    progress.save();
+    let visible = camera::visible_area(projection, camera_transform);
+    let position = spawning::point_on_border(visible, SPAWN_MARGIN, &mut rand::thread_rng());
+    let reaper = enemy::spawn_enemy_entity(&mut commands, &asset_server, EnemyKind::Reaper, position, None);
+    commands.entity(reaper).insert(Boss);
+}
+
+#[derive(Component)]
+pub struct GameOverScreen;
+
+// The run is over as soon as the player has no health left, whether the reaper came or not
+pub fn check_player_death(
+    player_query: Query<&Player, Changed<Player>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if player_query.iter().any(|player| player.health <= 0) {
+        next_state.set(GameState::GameOver);
+    }
+}
+
+// Show how the run went on top of the frozen game
+pub fn open_game_over_screen(mut commands: Commands, clock: Res<RunClock>, stats: Res<RunStats>) {
+    let title = if stats.victory { "Stage Cleared" } else { "Game Over" };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(20.0),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+                ..default()
+            },
+            GameOverScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                title,
+                TextStyle {
+                    font_size: 60.0,
+                    color: Color::RED,
+                    ..default()
+                },
+            ));
+            parent.spawn(TextBundle::from_section(
+                format!("Survived {} and defeated {} enemies", format_time(clock.elapsed), stats.kills),
+                TextStyle {
+                    font_size: 35.0,
+                    ..default()
+                },
+            ));
+        });
+}
@@ -0,0 +1,36 @@
+// This is the settings module, it holds options the player can change while playing
+
+use bevy::prelude::*;
+
+#[derive(Resource)]
+pub struct Settings {
+    // When on, weapons that support it fire towards the mouse cursor or right stick instead of aiming automatically
+    pub manual_aim: bool,
+    // Right stick input smaller than this is ignored
+    pub stick_deadzone: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            manual_aim: false,
+            stick_deadzone: 0.3,
+        }
+    }
+}
+
+// Toggle manual aiming with the M key or the gamepad's right stick button
+pub fn toggle_manual_aim(
+    mut settings: ResMut<Settings>,
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+) {
+    let stick_pressed = gamepads.iter().any(|gamepad| {
+        gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::RightThumb))
+    });
+    if keyboard_input.just_pressed(KeyCode::M) || stick_pressed {
+        settings.manual_aim = !settings.manual_aim;
+        info!("Manual aim: {}", settings.manual_aim);
+    }
+}
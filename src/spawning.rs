@@ -0,0 +1,150 @@
+// This is the spawning module, it picks where new enemies appear so they always start just outside of the screen
+// The visible area comes from the camera projection, so it follows window resizes
+
+use crate::map::SpawnZone;
+use crate::obstacle::MapBounds;
+use bevy::prelude::*;
+use rand::Rng;
+use std::f32::consts::TAU;
+
+// How far outside of the visible area enemies spawn
+pub const SPAWN_MARGIN: f32 = 50.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpawnPattern {
+    // A single enemy somewhere around the screen
+    Scattered,
+    // Enemies evenly spread on a circle around the screen, closing in from every side
+    Ring { count: usize },
+    // A line of enemies coming in from one side of the screen
+    Wall { count: usize },
+    // A tight group of enemies at one spot around the screen
+    Cluster { count: usize },
+}
+
+impl SpawnPattern {
+    // Most spawns are single enemies, the bigger patterns show up every now and then
+    pub fn random(rng: &mut impl Rng) -> Self {
+        match rng.gen::<f32>() {
+            roll if roll < 0.8 => SpawnPattern::Scattered,
+            roll if roll < 0.9 => SpawnPattern::Cluster { count: 5 },
+            roll if roll < 0.95 => SpawnPattern::Wall { count: 8 },
+            _ => SpawnPattern::Ring { count: 12 },
+        }
+    }
+
+    // Where every enemy of the pattern spawns, visible is the area the camera can see
+    pub fn points(&self, visible: Rect, rng: &mut impl Rng) -> Vec<Vec2> {
+        match *self {
+            SpawnPattern::Scattered => vec![point_on_border(visible, SPAWN_MARGIN, rng)],
+            SpawnPattern::Ring { count } => ring_points(visible, SPAWN_MARGIN, count, rng),
+            SpawnPattern::Wall { count } => wall_points(visible, SPAWN_MARGIN, count, rng),
+            SpawnPattern::Cluster { count } => cluster_points(visible, SPAWN_MARGIN, count, rng),
+        }
+    }
+}
+
+// A uniformly random point on the border of the visible area grown by margin on every side
+pub fn point_on_border(visible: Rect, margin: f32, rng: &mut impl Rng) -> Vec2 {
+    let area = visible.inset(margin);
+    let width = area.width();
+    let height = area.height();
+    // Walk along the perimeter, so longer sides get more spawns than shorter ones
+    let along = rng.gen_range(0.0..2.0 * (width + height));
+    if along < width {
+        Vec2::new(area.min.x + along, area.max.y)
+    } else if along < 2.0 * width {
+        Vec2::new(area.min.x + along - width, area.min.y)
+    } else if along < 2.0 * width + height {
+        Vec2::new(area.min.x, area.min.y + along - 2.0 * width)
+    } else {
+        Vec2::new(area.max.x, area.min.y + along - 2.0 * width - height)
+    }
+}
+
+// Points evenly spread on a circle that fully surrounds the visible area
+pub fn ring_points(visible: Rect, margin: f32, count: usize, rng: &mut impl Rng) -> Vec<Vec2> {
+    let radius = visible.half_size().length() + margin;
+    let start = rng.gen_range(0.0..TAU);
+    (0..count)
+        .map(|index| {
+            let angle = start + TAU * index as f32 / count as f32;
+            visible.center() + Vec2::from_angle(angle) * radius
+        })
+        .collect()
+}
+
+// Points evenly spread along one random side of the visible area
+pub fn wall_points(visible: Rect, margin: f32, count: usize, rng: &mut impl Rng) -> Vec<Vec2> {
+    let area = visible.inset(margin);
+    let (start, end) = match rng.gen_range(0..4) {
+        0 => (Vec2::new(area.min.x, area.max.y), area.max),
+        1 => (area.min, Vec2::new(area.max.x, area.min.y)),
+        2 => (area.min, Vec2::new(area.min.x, area.max.y)),
+        _ => (Vec2::new(area.max.x, area.min.y), area.max),
+    };
+    (0..count)
+        .map(|index| start.lerp(end, (index as f32 + 0.5) / count as f32))
+        .collect()
+}
+
+// Points close together around one spot, pushed further out so the whole group starts off screen
+pub fn cluster_points(visible: Rect, margin: f32, count: usize, rng: &mut impl Rng) -> Vec<Vec2> {
+    let spread = 40.0;
+    let center = point_on_border(visible, margin + spread, rng);
+    (0..count)
+        .map(|_| {
+            let angle = rng.gen_range(0.0..TAU);
+            center + Vec2::from_angle(angle) * rng.gen_range(0.0..spread)
+        })
+        .collect()
+}
+
+// Fit the points of a pattern into where enemies can come from, size is the size of the enemy
+// With a spawn zone the whole pattern is moved into the zone, keeping its shape as far as the zone allows
+// Otherwise the points are kept inside the map, and points the map edge pulls into view are moved back out of view
+pub fn place_points(
+    points: Vec<Vec2>,
+    zone: Option<&SpawnZone>,
+    visible: Rect,
+    bounds: &MapBounds,
+    size: Vec2,
+    rng: &mut impl Rng,
+) -> Vec<Vec2> {
+    if points.is_empty() {
+        return points;
+    }
+    if let Some(zone) = zone {
+        let center = points.iter().sum::<Vec2>() / points.len() as f32;
+        let offset = zone.random_point(rng) - center;
+        return points
+            .into_iter()
+            .map(|point| (point + offset).clamp(zone.area.min, zone.area.max))
+            .collect();
+    }
+    points
+        .into_iter()
+        .map(|point| out_of_view(point, visible, bounds, size))
+        .collect()
+}
+
+// Keep a spawn point inside the map, if that puts it in view it goes just outside of the closest side the map has room on
+// When the whole map is visible there is nowhere to hide, so the enemy comes in from the edge of the map after all
+fn out_of_view(point: Vec2, visible: Rect, bounds: &MapBounds, size: Vec2) -> Vec2 {
+    let position = bounds.clamp(point, size);
+    if !visible.contains(position) {
+        return position;
+    }
+    let area = visible.inset(SPAWN_MARGIN);
+    [
+        Vec2::new(area.min.x, position.y),
+        Vec2::new(area.max.x, position.y),
+        Vec2::new(position.x, area.min.y),
+        Vec2::new(position.x, area.max.y),
+    ]
+    .into_iter()
+    .map(|side| bounds.clamp(side, size))
+    .filter(|side| !visible.contains(*side))
+    .min_by(|a, b| a.distance_squared(point).total_cmp(&b.distance_squared(point)))
+    .unwrap_or(position)
+}
@@ -0,0 +1,275 @@
+// This is the stage module, every stage has its own map, ground, enemies and wave timeline
+// The stages are listed in assets/data/stages.ron, the first screen of the game lets the player pick one
+// Stages after the first are unlocked by doing well in earlier runs, see the progress module
+
+use crate::data;
+use crate::enemy::EnemyKind;
+use crate::game_state::GameState;
+use crate::ground::Biome;
+use crate::progress::Progress;
+use crate::run::RunStats;
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+
+pub const STAGES_PATH: &str = "assets/data/stages.ron";
+
+// A point on the wave timeline, it lasts until the next wave starts
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Wave {
+    // Seconds into the run the wave starts
+    pub start: f32,
+    // Seconds between spawn patterns
+    pub spawn_interval: f32,
+}
+
+// What has to happen in earlier runs before a stage can be picked
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub enum Unlock {
+    Always,
+    // Reach this level on another stage
+    Level { stage: String, level: i32 },
+    // Survive this many minutes on another stage
+    Survive { stage: String, minutes: u32 },
+    // Survive another stage until the end of the run
+    Clear { stage: String },
+    // Kill this many enemies over all runs
+    Kills(u32),
+}
+
+impl Unlock {
+    pub fn is_met(&self, progress: &Progress) -> bool {
+        match self {
+            Unlock::Always => true,
+            Unlock::Level { stage, level } => progress.record(stage).best_level >= *level,
+            Unlock::Survive { stage, minutes } => progress.record(stage).best_minutes >= *minutes,
+            Unlock::Clear { stage } => progress.record(stage).cleared,
+            Unlock::Kills(kills) => progress.total_kills >= *kills,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Unlock::Always => String::new(),
+            Unlock::Level { stage, level } => format!("Reach level {} in {}", level, stage),
+            Unlock::Survive { stage, minutes } => format!("Survive {} minutes in {}", minutes, stage),
+            Unlock::Clear { stage } => format!("Clear {}", stage),
+            Unlock::Kills(kills) => format!("Defeat {} enemies", kills),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Stage {
+    pub name: String,
+    // Path of the Tiled map file
+    pub map: String,
+    // Replaces the ground of the map, so the same map can be reused with other tiles
+    #[serde(default)]
+    pub ground: Option<Biome>,
+    // The kinds of enemies that spawn on the stage and how often they spawn
+    pub enemies: Vec<(EnemyKind, f32)>,
+    pub waves: Vec<Wave>,
+    // Seconds until the run is won and the reaper comes
+    #[serde(default = "default_run_length")]
+    pub run_length: f32,
+    pub unlock: Unlock,
+}
+
+fn default_run_length() -> f32 {
+    30.0 * 60.0
+}
+
+impl Stage {
+    // Pick a kind of enemy from the stage's pool based on their weights
+    pub fn random_enemy(&self, rng: &mut impl Rng) -> EnemyKind {
+        let total: f32 = self.enemies.iter().map(|(_, weight)| weight).sum();
+        let mut pick = rng.gen_range(0.0..total.max(f32::EPSILON));
+        for (kind, weight) in &self.enemies {
+            if pick < *weight {
+                return *kind;
+            }
+            pick -= weight;
+        }
+        self.enemies.last().map_or(EnemyKind::Bat, |(kind, _)| *kind)
+    }
+
+    // The wave that is running at the given time into the run
+    pub fn wave(&self, elapsed: f32) -> Option<&Wave> {
+        self.waves.iter().rev().find(|wave| wave.start <= elapsed)
+    }
+}
+
+#[derive(Resource, Deserialize)]
+#[serde(transparent)]
+pub struct StageRegistry(pub Vec<Stage>);
+
+impl StageRegistry {
+    pub fn from_ron(source: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(source)
+    }
+}
+
+// The stage being played, or highlighted on the stage selection screen
+#[derive(Resource)]
+pub struct SelectedStage(pub Stage);
+
+// Seconds of gameplay since the stage started, it doesn't count while the game is paused
+#[derive(Resource, Default)]
+pub struct RunClock {
+    pub elapsed: f32,
+}
+
+// The only stage left when the stage list can't be read, an endless forest full of bats
+pub fn fallback_stage() -> Stage {
+    Stage {
+        name: "Forest".to_string(),
+        map: "assets/maps/forest.json".to_string(),
+        ground: None,
+        enemies: vec![(EnemyKind::Bat, 1.0)],
+        waves: vec![Wave {
+            start: 0.0,
+            spawn_interval: 2.0,
+        }],
+        run_length: default_run_length(),
+        unlock: Unlock::Always,
+    }
+}
+
+// Read the stages from disk when the game starts
+pub fn load_stages(mut commands: Commands) {
+    let mut stages = data::read(STAGES_PATH, StageRegistry::from_ron).unwrap_or_else(|error| {
+        error!("{}", error);
+        StageRegistry(Vec::new())
+    });
+    if stages.0.is_empty() {
+        warn!("There are no stages in {}, only the fallback stage can be played", STAGES_PATH);
+        stages.0.push(fallback_stage());
+    }
+    let first = stages.0[0].clone();
+    commands.insert_resource(SelectedStage(first));
+    commands.insert_resource(stages);
+}
+
+pub fn tick_run_clock(time: Res<Time>, mut clock: ResMut<RunClock>) {
+    clock.elapsed += time.delta_seconds();
+}
+
+#[derive(Component)]
+pub struct StageSelectScreen;
+
+// One entry in the list of stages
+#[derive(Component)]
+pub struct StageEntry {
+    pub index: usize,
+}
+
+// The index of the highlighted stage on the selection screen
+#[derive(Resource, Default)]
+pub struct StageCursor(pub usize);
+
+pub fn open_stage_select(mut commands: Commands, stages: Res<StageRegistry>, progress: Res<Progress>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(20.0),
+                    ..default()
+                },
+                background_color: Color::rgb(0.05, 0.05, 0.1).into(),
+                ..default()
+            },
+            StageSelectScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Select a Stage",
+                TextStyle {
+                    font_size: 60.0,
+                    color: Color::GOLD,
+                    ..default()
+                },
+            ));
+            for (index, stage) in stages.0.iter().enumerate() {
+                let label = if stage.unlock.is_met(&progress) {
+                    stage.name.clone()
+                } else {
+                    format!("Locked - {}", stage.unlock.describe())
+                };
+                parent.spawn((
+                    TextBundle::from_section(
+                        label,
+                        TextStyle {
+                            font_size: 35.0,
+                            ..default()
+                        },
+                    ),
+                    StageEntry { index },
+                ));
+            }
+            parent.spawn(TextBundle::from_section(
+                "Up and down to choose, Space to start",
+                TextStyle {
+                    font_size: 25.0,
+                    color: Color::GRAY,
+                    ..default()
+                },
+            ));
+        });
+    commands.init_resource::<StageCursor>();
+}
+
+// Move the highlight with the keyboard or the d-pad, and start the highlighted stage once it's unlocked
+pub fn navigate_stage_select(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    stages: Res<StageRegistry>,
+    progress: Res<Progress>,
+    mut cursor: ResMut<StageCursor>,
+    mut selected: ResMut<SelectedStage>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut entry_query: Query<(&StageEntry, &mut Text)>,
+) {
+    let pressed = |keys: [KeyCode; 2], button: GamepadButtonType| {
+        keyboard_input.any_just_pressed(keys)
+            || gamepad_buttons
+                .get_just_pressed()
+                .any(|pressed| pressed.button_type == button)
+    };
+    let count = stages.0.len();
+    if pressed([KeyCode::Up, KeyCode::W], GamepadButtonType::DPadUp) {
+        cursor.0 = (cursor.0 + count - 1) % count;
+    }
+    if pressed([KeyCode::Down, KeyCode::S], GamepadButtonType::DPadDown) {
+        cursor.0 = (cursor.0 + 1) % count;
+    }
+    for (entry, mut text) in entry_query.iter_mut() {
+        let unlocked = stages.0[entry.index].unlock.is_met(&progress);
+        text.sections[0].style.color = match (entry.index == cursor.0, unlocked) {
+            (true, true) => Color::GOLD,
+            (false, true) => Color::WHITE,
+            (true, false) => Color::rgb(0.6, 0.5, 0.3),
+            (false, false) => Color::DARK_GRAY,
+        };
+    }
+    let stage = &stages.0[cursor.0];
+    if pressed([KeyCode::Space, KeyCode::Return], GamepadButtonType::South) && stage.unlock.is_met(&progress) {
+        selected.0 = stage.clone();
+        next_state.set(GameState::Playing);
+    }
+}
+
+pub fn close_stage_select(mut commands: Commands, screen_query: Query<Entity, With<StageSelectScreen>>) {
+    for screen in screen_query.iter() {
+        commands.entity(screen).despawn_recursive();
+    }
+    commands.remove_resource::<StageCursor>();
+    commands.insert_resource(RunClock::default());
+    commands.insert_resource(RunStats::default());
+}
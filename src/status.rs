@@ -0,0 +1,254 @@
+// This is the status module, status effects are temporary conditions on the player or enemies like being frozen or poisoned
+// Anything that wants to apply a status sends a StatusEvent, the same way damage goes through DamageEvent
+
+use crate::damage::{DamageEvent, DamageKind};
+use crate::enemy::Enemy;
+use bevy::prelude::*;
+
+// How many poison stacks an entity can have at once
+pub const MAX_POISON_STACKS: usize = 5;
+// Seconds between burn and poison damage ticks
+pub const DAMAGE_TICK_RATE: f32 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatusKind {
+    // Can't move
+    Freeze,
+    // Moves slower, potency is the speed multiplier
+    Slow,
+    // Takes potency damage every tick, reapplying only refreshes it
+    Burn,
+    // Takes potency damage every tick for every stack, reapplying adds a stack
+    Poison,
+    // Takes more damage, potency is the damage multiplier
+    Weaken,
+    // Can't be hurt
+    Invulnerable,
+}
+
+impl StatusKind {
+    // The color an affected sprite is tinted with
+    pub fn tint(&self) -> Color {
+        match self {
+            StatusKind::Freeze => Color::rgb(0.5, 0.8, 1.0),
+            StatusKind::Slow => Color::rgb(0.6, 0.6, 1.0),
+            StatusKind::Burn => Color::rgb(1.0, 0.5, 0.2),
+            StatusKind::Poison => Color::rgb(0.4, 1.0, 0.4),
+            StatusKind::Weaken => Color::rgb(0.8, 0.4, 1.0),
+            StatusKind::Invulnerable => Color::rgb(1.0, 1.0, 0.6),
+        }
+    }
+}
+
+// A status that can be applied, weapons carry one of these to apply on hit
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    // Seconds the status lasts
+    pub duration: f32,
+    // What this means depends on the kind, see StatusKind
+    pub potency: f32,
+}
+
+#[derive(Event)]
+pub struct StatusEvent {
+    pub target: Entity,
+    pub effect: StatusEffect,
+}
+
+pub struct ActiveStatus {
+    pub kind: StatusKind,
+    pub potency: f32,
+    pub timer: Timer,
+}
+
+// Every status currently on an entity, entities without this component can't receive statuses
+#[derive(Component)]
+pub struct StatusEffects {
+    pub active: Vec<ActiveStatus>,
+    pub damage_tick: Timer,
+}
+
+impl Default for StatusEffects {
+    fn default() -> Self {
+        StatusEffects {
+            active: Vec::new(),
+            damage_tick: Timer::from_seconds(DAMAGE_TICK_RATE, TimerMode::Repeating),
+        }
+    }
+}
+
+impl StatusEffects {
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.active.iter().any(|status| status.kind == kind)
+    }
+
+    pub fn stacks(&self, kind: StatusKind) -> usize {
+        self.active.iter().filter(|status| status.kind == kind).count()
+    }
+
+    // The strongest potency of the given kind, None when it's not active
+    fn strongest(&self, kind: StatusKind, stronger: fn(f32, f32) -> f32) -> Option<f32> {
+        self.active
+            .iter()
+            .filter(|status| status.kind == kind)
+            .map(|status| status.potency)
+            .reduce(stronger)
+    }
+
+    // Freeze stops movement entirely, otherwise the strongest slow is used
+    pub fn movement_multiplier(&self) -> f32 {
+        if self.has(StatusKind::Freeze) {
+            return 0.0;
+        }
+        self.strongest(StatusKind::Slow, f32::min).unwrap_or(1.0)
+    }
+
+    pub fn damage_taken_multiplier(&self) -> f32 {
+        self.strongest(StatusKind::Weaken, f32::max).unwrap_or(1.0)
+    }
+
+    // Damage done by burn and poison every tick, burn doesn't stack but every poison stack counts
+    pub fn damage_per_tick(&self) -> f32 {
+        self.burn_damage() + self.poison_damage()
+    }
+
+    pub fn burn_damage(&self) -> f32 {
+        self.strongest(StatusKind::Burn, f32::max).unwrap_or(0.0)
+    }
+
+    pub fn poison_damage(&self) -> f32 {
+        self.active
+            .iter()
+            .filter(|status| status.kind == StatusKind::Poison)
+            .map(|status| status.potency)
+            .sum()
+    }
+
+    // Add a status following its stacking rule
+    pub fn apply(&mut self, effect: StatusEffect) {
+        let timer = Timer::from_seconds(effect.duration, TimerMode::Once);
+        if effect.kind == StatusKind::Poison {
+            if self.stacks(StatusKind::Poison) < MAX_POISON_STACKS {
+                self.active.push(ActiveStatus {
+                    kind: effect.kind,
+                    potency: effect.potency,
+                    timer,
+                });
+            } else if let Some(oldest) = self
+                .active
+                .iter_mut()
+                .filter(|status| status.kind == StatusKind::Poison)
+                .min_by(|a, b| a.timer.remaining_secs().total_cmp(&b.timer.remaining_secs()))
+            {
+                // At max stacks the stack closest to running out is refreshed instead
+                oldest.potency = oldest.potency.max(effect.potency);
+                oldest.timer = timer;
+            }
+            return;
+        }
+        match self.active.iter_mut().find(|status| status.kind == effect.kind) {
+            Some(status) => {
+                // Never shorten a status that has more time left, and keep the stronger potency
+                if status.timer.remaining_secs() < effect.duration {
+                    status.timer = timer;
+                }
+                status.potency = match effect.kind {
+                    StatusKind::Slow => status.potency.min(effect.potency),
+                    _ => status.potency.max(effect.potency),
+                };
+            }
+            None => self.active.push(ActiveStatus {
+                kind: effect.kind,
+                potency: effect.potency,
+                timer,
+            }),
+        }
+    }
+}
+
+// The color a sprite has when no status is tinting it
+#[derive(Component)]
+pub struct BaseColor(pub Color);
+
+// Add the statuses sent this frame, enemies ignore the statuses their kind is immune to
+pub fn apply_status(
+    mut status_events: EventReader<StatusEvent>,
+    mut status_query: Query<(&mut StatusEffects, Option<&Enemy>)>,
+) {
+    for event in status_events.read() {
+        let Ok((mut statuses, enemy)) = status_query.get_mut(event.target) else {
+            continue;
+        };
+        if let Some(enemy) = enemy {
+            if enemy.kind.immunities().contains(&event.effect.kind) {
+                continue;
+            }
+        }
+        statuses.apply(event.effect);
+    }
+}
+
+// Count down every status, deal burn and poison damage, and remove the statuses that ran out
+pub fn tick_status_effects(
+    time: Res<Time>,
+    mut status_query: Query<(Entity, &mut StatusEffects)>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for (entity, mut statuses) in status_query.iter_mut() {
+        if statuses.active.is_empty() {
+            continue;
+        }
+        let damage = statuses.damage_per_tick();
+        if damage > 0.0 && statuses.damage_tick.tick(time.delta()).just_finished() {
+            // Burn and poison are sent apart so their damage numbers get their own colors
+            // The total is rounded once, so splitting it doesn't change how much damage is done
+            let total = damage.round().max(1.0) as i32;
+            let burn = if statuses.poison_damage() > 0.0 {
+                (statuses.burn_damage().round() as i32).min(total)
+            } else {
+                total
+            };
+            for (kind, amount) in [(DamageKind::Fire, burn), (DamageKind::Poison, total - burn)] {
+                if amount > 0 {
+                    damage_events.send(DamageEvent {
+                        target: entity,
+                        amount,
+                        kind,
+                        critical: false,
+                        lethal: false,
+                    });
+                }
+            }
+        }
+        for status in statuses.active.iter_mut() {
+            status.timer.tick(time.delta());
+        }
+        statuses.active.retain(|status| !status.timer.finished());
+    }
+}
+
+// Tint sprites with the color of their most important status, or go back to their base color
+pub fn tint_status_effects(
+    mut sprite_query: Query<(&StatusEffects, &mut Sprite, Option<&BaseColor>), Changed<StatusEffects>>,
+) {
+    // Earlier kinds win when several statuses are active
+    let order = [
+        StatusKind::Freeze,
+        StatusKind::Invulnerable,
+        StatusKind::Burn,
+        StatusKind::Poison,
+        StatusKind::Weaken,
+        StatusKind::Slow,
+    ];
+    for (statuses, mut sprite, base_color) in sprite_query.iter_mut() {
+        let color = order
+            .iter()
+            .find(|kind| statuses.has(**kind))
+            .map(|kind| kind.tint())
+            .unwrap_or(base_color.map_or(Color::WHITE, |base_color| base_color.0));
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
+}
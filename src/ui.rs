@@ -1,6 +1,15 @@
-// UI module for the game, the ui will show the player's health, experience, and level
+// UI module for the game, the ui will show the player's health, experience, level, kills, gold and the run timer
+// The health bar floats above the player and the experience bar fills the top of the screen
+// The weapons the player owns are shown as a row of icons with their levels at the bottom of the screen
 
+use crate::hazard::HazardWeapon;
+use crate::player::Player;
+use crate::run::{self, RunStats};
+use crate::stage::{RunClock, SelectedStage};
+use crate::weapon::Weapon;
 use bevy::prelude::*;
+use bevy::ui::UiScale;
+use bevy::window::PrimaryWindow;
 
 #[derive(Component)]
 pub struct UI;
@@ -14,27 +23,132 @@ pub struct ExperienceText;
 #[derive(Component)]
 pub struct LevelText;
 
+#[derive(Component)]
+pub struct RunTimerText;
+
+#[derive(Component)]
+pub struct KillText;
+
+#[derive(Component)]
+pub struct GoldText;
+
+// The row of weapon icons, its children are rebuilt whenever a weapon changes
+#[derive(Component)]
+pub struct ItemBar;
+
+// The health bar floats above the player in the world, the fill is a child of the background
+#[derive(Component)]
+pub struct HealthBar;
+
+#[derive(Component)]
+pub struct HealthBarFill;
+
+// The experience bar spans the whole top of the screen
+#[derive(Component)]
+pub struct ExperienceBarFill;
+
+pub const HEALTH_BAR_SIZE: Vec2 = Vec2::new(60.0, 8.0);
+// How far above the center of the player the health bar floats
+pub const HEALTH_BAR_OFFSET: f32 = 40.0;
+pub const EXPERIENCE_BAR_HEIGHT: f32 = 14.0;
+pub const ITEM_ICON_SIZE: f32 = 48.0;
+// The window height the HUD is laid out for, taller or shorter windows scale the whole UI
+pub const REFERENCE_HEIGHT: f32 = 720.0;
+
+// One icon in the item bar
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItemSlot {
+    pub name: String,
+    // Hazard weapons have no texture, their icon is a square in the hazard's color
+    pub texture: Option<String>,
+    pub color: Color,
+    pub level: Option<u32>,
+}
+
+impl ItemSlot {
+    pub fn label(&self) -> String {
+        self.level.map_or(String::new(), |level| format!("Lv {}", level))
+    }
+}
+
+// Weapons and hazard weapons in the order they were picked up
+// Queries don't keep any order, so everything is sorted by its slot
+pub fn item_slots<'a>(
+    weapons: impl Iterator<Item = &'a Weapon>,
+    hazard_weapons: impl Iterator<Item = &'a HazardWeapon>,
+) -> Vec<ItemSlot> {
+    let weapons = weapons.map(|weapon| {
+        let slot = ItemSlot {
+            name: weapon.name.clone(),
+            texture: Some(weapon.texture.clone()),
+            color: Color::WHITE,
+            level: Some(weapon.level),
+        };
+        (weapon.slot, slot)
+    });
+    let hazard_weapons = hazard_weapons.map(|weapon| {
+        let slot = ItemSlot {
+            name: weapon.name.clone(),
+            texture: None,
+            color: weapon.color.with_a(1.0),
+            level: None,
+        };
+        (weapon.slot, slot)
+    });
+    let mut slots: Vec<(u32, ItemSlot)> = weapons.chain(hazard_weapons).collect();
+    slots.sort_by_key(|(order, _)| *order);
+    slots.into_iter().map(|(_, slot)| slot).collect()
+}
+
 pub fn spawn_ui(
     commands: &mut Commands,
-    asset_server: Res<AssetServer>,
+    _asset_server: Res<AssetServer>,
 ) {
-    // Spawn the health text
-    commands.spawn((
-        TextBundle::from_section(
-            "Health: 100",
-            TextStyle {
-                font_size: 40.0,
+    // Spawn the health, kill and gold texts in a column in the top left
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(EXPERIENCE_BAR_HEIGHT + 10.0),
+                left: Val::Px(10.0),
+                flex_direction: FlexDirection::Column,
                 ..default()
             },
-        )
-        .with_style(Style {
-            position_type: PositionType::Absolute,
-            top: Val::Px(10.0),
-            left: Val::Px(10.0),
             ..default()
-        }),
-        HealthText,
-    ));
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "Health: 100",
+                    TextStyle {
+                        font_size: 40.0,
+                        ..default()
+                    },
+                ),
+                HealthText,
+            ));
+            parent.spawn((
+                TextBundle::from_section(
+                    "Kills: 0",
+                    TextStyle {
+                        font_size: 30.0,
+                        ..default()
+                    },
+                ),
+                KillText,
+            ));
+            parent.spawn((
+                TextBundle::from_section(
+                    "Gold: 0",
+                    TextStyle {
+                        font_size: 30.0,
+                        color: Color::GOLD,
+                        ..default()
+                    },
+                ),
+                GoldText,
+            ));
+        });
 
     // Spawn the experience text
     commands.spawn((
@@ -47,7 +161,7 @@ pub fn spawn_ui(
         )
         .with_style(Style {
             position_type: PositionType::Absolute,
-            top: Val::Px(10.0),
+            top: Val::Px(EXPERIENCE_BAR_HEIGHT + 10.0),
             right: Val::Px(10.0),
             ..default()
         }),
@@ -71,4 +185,308 @@ pub fn spawn_ui(
         }),
         LevelText,
     ));
+
+    // Spawn the run timer, centered at the top of the screen
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(EXPERIENCE_BAR_HEIGHT + 10.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "00:00",
+                    TextStyle {
+                        font_size: 40.0,
+                        ..default()
+                    },
+                ),
+                RunTimerText,
+            ));
+        });
+
+    // Spawn the item bar in the bottom left, the icons are added by update_item_bar
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                left: Val::Px(10.0),
+                column_gap: Val::Px(6.0),
+                ..default()
+            },
+            ..default()
+        },
+        ItemBar,
+    ));
+
+    // Spawn the experience bar, the fill grows towards the next level
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(0.0),
+                width: Val::Percent(100.0),
+                height: Val::Px(EXPERIENCE_BAR_HEIGHT),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(0.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.3, 0.5, 1.0).into(),
+                    ..default()
+                },
+                ExperienceBarFill,
+            ));
+        });
+
+    // Spawn the health bar, it is attached to the player on the first frame of the run
+    commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(0.0, 0.0, 0.0, 0.7),
+                    custom_size: Some(HEALTH_BAR_SIZE),
+                    ..default()
+                },
+                // Drawn above the player and enemies
+                transform: Transform::from_xyz(0.0, HEALTH_BAR_OFFSET, 5.0),
+                ..default()
+            },
+            HealthBar,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::GREEN,
+                        custom_size: Some(HEALTH_BAR_SIZE),
+                        // Anchored on the left so the bar shrinks towards the left
+                        anchor: bevy::sprite::Anchor::CenterLeft,
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(-HEALTH_BAR_SIZE.x / 2.0, 0.0, 0.1),
+                    ..default()
+                },
+                HealthBarFill,
+            ));
+        });
+}
+
+// Write a label only when its text is different, walking changes the player but not what the labels show
+fn set_label(text: &mut Mut<Text>, label: String) {
+    if text.sections[0].value != label {
+        text.sections[0].value = label;
+    }
+}
+
+// The labels are only rewritten when the player changed, gameplay systems never touch the UI
+pub fn update_health_text(
+    player_query: Query<&Player, Changed<Player>>,
+    mut text_query: Query<&mut Text, With<HealthText>>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    for mut text in text_query.iter_mut() {
+        set_label(&mut text, format!("Health: {}", player.health));
+    }
+}
+
+pub fn update_experience_text(
+    player_query: Query<&Player, Changed<Player>>,
+    mut text_query: Query<&mut Text, With<ExperienceText>>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    for mut text in text_query.iter_mut() {
+        set_label(&mut text, format!("Experience: {}", player.experience));
+    }
+}
+
+pub fn update_level_text(
+    player_query: Query<&Player, Changed<Player>>,
+    mut text_query: Query<&mut Text, With<LevelText>>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    for mut text in text_query.iter_mut() {
+        set_label(&mut text, format!("Level: {}", player.level));
+    }
+}
+
+pub fn update_gold_text(
+    player_query: Query<&Player, Changed<Player>>,
+    mut text_query: Query<&mut Text, With<GoldText>>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    for mut text in text_query.iter_mut() {
+        set_label(&mut text, format!("Gold: {}", player.gold));
+    }
+}
+
+pub fn update_kill_text(stats: Res<RunStats>, mut text_query: Query<&mut Text, With<KillText>>) {
+    if !stats.is_changed() {
+        return;
+    }
+    for mut text in text_query.iter_mut() {
+        set_label(&mut text, format!("Kills: {}", stats.kills));
+    }
+}
+
+// Rebuild the icons when a weapon was added, leveled up or removed
+pub fn update_item_bar(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    bar_query: Query<Entity, With<ItemBar>>,
+    changed_weapons: Query<(), Changed<Weapon>>,
+    changed_hazard_weapons: Query<(), Changed<HazardWeapon>>,
+    mut removed_weapons: RemovedComponents<Weapon>,
+    mut removed_hazard_weapons: RemovedComponents<HazardWeapon>,
+    weapon_query: Query<&Weapon>,
+    hazard_weapon_query: Query<&HazardWeapon>,
+) {
+    // Both readers are always read, so old removals don't trigger a rebuild later on
+    let removed = removed_weapons.read().count() + removed_hazard_weapons.read().count();
+    if changed_weapons.is_empty() && changed_hazard_weapons.is_empty() && removed == 0 {
+        return;
+    }
+    let slots = item_slots(weapon_query.iter(), hazard_weapon_query.iter());
+    for bar in bar_query.iter() {
+        commands.entity(bar).despawn_descendants().with_children(|parent| {
+            for slot in &slots {
+                let icon = Style {
+                    width: Val::Px(ITEM_ICON_SIZE),
+                    height: Val::Px(ITEM_ICON_SIZE),
+                    // The level sits in the bottom right corner of the icon
+                    justify_content: JustifyContent::FlexEnd,
+                    align_items: AlignItems::FlexEnd,
+                    ..default()
+                };
+                let mut icon = match &slot.texture {
+                    Some(texture) => parent.spawn(ImageBundle {
+                        style: icon,
+                        image: asset_server.load(texture).into(),
+                        background_color: slot.color.into(),
+                        ..default()
+                    }),
+                    None => parent.spawn(NodeBundle {
+                        style: icon,
+                        background_color: slot.color.into(),
+                        ..default()
+                    }),
+                };
+                icon.with_children(|icon| {
+                    icon.spawn(TextBundle::from_section(
+                        slot.label(),
+                        TextStyle {
+                            font_size: 18.0,
+                            ..default()
+                        },
+                    ));
+                });
+            }
+        });
+    }
+}
+
+// Scale the whole UI with the height of the window, so the HUD takes up the same part of the screen at any size
+pub fn scale_ui(
+    window_query: Query<&Window, (With<PrimaryWindow>, Changed<Window>)>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    ui_scale.0 = (window.height() / REFERENCE_HEIGHT).max(0.5) as f64;
+}
+
+// Make the health bar a child of the player so it moves along without being copied every frame
+// The player sprite is scaled, so the bar undoes that scale to keep its size and offset
+pub fn attach_health_bar(
+    mut commands: Commands,
+    player_query: Query<(Entity, &Transform), (Added<Player>, Without<HealthBar>)>,
+    mut bar_query: Query<(Entity, &mut Transform), With<HealthBar>>,
+) {
+    let Ok((player, player_transform)) = player_query.get_single() else {
+        return;
+    };
+    let scale = player_transform.scale;
+    for (bar, mut transform) in bar_query.iter_mut() {
+        transform.translation = Vec3::new(0.0, HEALTH_BAR_OFFSET / scale.y, 5.0 / scale.z);
+        transform.scale = Vec3::ONE / scale;
+        commands.entity(bar).set_parent(player);
+    }
+}
+
+// Resize the health bar when the player's health changed, it goes from green to red as it empties
+pub fn update_health_bar(
+    player_query: Query<&Player, Changed<Player>>,
+    mut fill_query: Query<&mut Sprite, With<HealthBarFill>>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    let fraction = (player.health as f32 / player.max_health.max(1) as f32).clamp(0.0, 1.0);
+    let size = Some(Vec2::new(HEALTH_BAR_SIZE.x * fraction, HEALTH_BAR_SIZE.y));
+    for mut sprite in fill_query.iter_mut() {
+        // Moving or picking up gold also changes the player, the bar is only touched when health did
+        if sprite.custom_size != size {
+            sprite.custom_size = size;
+            sprite.color = Color::rgb(1.0 - fraction, fraction, 0.0);
+        }
+    }
+}
+
+// Fill the experience bar when the player's experience changed
+pub fn update_experience_bar(
+    player_query: Query<&Player, Changed<Player>>,
+    mut fill_query: Query<&mut Style, With<ExperienceBarFill>>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    let fraction = (player.experience as f32 / player.experience_to_next_level.max(1) as f32).clamp(0.0, 1.0);
+    let width = Val::Percent(fraction * 100.0);
+    for mut style in fill_query.iter_mut() {
+        if style.width != width {
+            style.width = width;
+        }
+    }
+}
+
+// Show the run clock, it turns red once the run is over
+pub fn update_run_timer(
+    clock: Res<RunClock>,
+    stage: Res<SelectedStage>,
+    mut timer_query: Query<&mut Text, With<RunTimerText>>,
+) {
+    if !clock.is_changed() {
+        return;
+    }
+    // The clock changes every frame but the label only every second, so it is only written when the text differs
+    for mut text in timer_query.iter_mut() {
+        set_label(&mut text, run::format_time(clock.elapsed));
+        if clock.elapsed >= stage.0.run_length && text.sections[0].style.color != Color::RED {
+            text.sections[0].style.color = Color::RED;
+        }
+    }
 }
@@ -0,0 +1,324 @@
+// This is the weapon module, a weapon describes what kind of projectile gets fired when its timer finishes
+
+use crate::projectile::ProjectileMotion;
+use crate::status::{StatusEffect, StatusKind};
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+// How a weapon picks the direction to fire in
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Targeting {
+    // The enemy closest to the player
+    Nearest,
+    // Any enemy, picked at random for every projectile
+    Random,
+    // The enemy with the most health
+    Strongest,
+    // The enemy with the least health
+    LowestHealth,
+    // The direction the player last moved in
+    Facing,
+    // Towards the mouse cursor
+    MouseCursor,
+}
+
+#[derive(Component)]
+pub struct Weapon {
+    pub name: String,
+    pub texture: String,
+    // How fast the projectile leaves the player
+    pub projectile_speed: f32,
+    // How the projectile moves once it has been fired
+    pub motion: ProjectileMotion,
+    // How many seconds a projectile lives before it is despawned
+    pub projectile_lifetime: f32,
+    // How many projectiles are fired every time the timer finishes
+    pub amount: u32,
+    // Angle in degrees between the projectiles of a volley
+    pub spread: f32,
+    // Seconds between the projectiles of a volley, zero fires them all at once
+    pub volley_delay: f32,
+    pub targeting: Targeting,
+    // Whether this weapon fires where the player aims when manual aiming is turned on
+    pub manual_aim: bool,
+    // Status applied to the enemies its projectiles hit
+    pub on_hit: Option<StatusEffect>,
+    // Chance from 0 to 1 that a projectile lands a critical hit, which does double damage
+    pub crit_chance: f32,
+    pub level: u32,
+    // A weapon at MAX_WEAPON_LEVEL can evolve once into a stronger version of itself
+    pub evolved: bool,
+    pub slot: u32,
+}
+
+pub const MAX_WEAPON_LEVEL: u32 = 8;
+// The most weapons the player can carry, chests stop handing out new ones after that
+pub const MAX_WEAPONS: usize = 6;
+
+// Hands out item bar slots as weapons and hazard weapons are picked up
+// Both kinds count up from the same counter, so the item bar can show them in the order they were picked up
+#[derive(Resource, Default)]
+pub struct NextSlot(pub u32);
+
+impl NextSlot {
+    pub fn take(&mut self) -> u32 {
+        self.0 += 1;
+        self.0 - 1
+    }
+}
+
+impl Weapon {
+    // Every level makes projectiles faster, every other level adds a projectile to the volley
+    pub fn level_up(&mut self) {
+        if self.level >= MAX_WEAPON_LEVEL {
+            return;
+        }
+        self.level += 1;
+        self.projectile_speed *= 1.1;
+        if self.level.is_multiple_of(2) {
+            self.amount += 1;
+        }
+    }
+
+    // The name the weapon had before it evolved, the same for every copy of a weapon
+    pub fn base_name(&self) -> &str {
+        self.name.strip_prefix("Evolved ").unwrap_or(&self.name)
+    }
+
+    pub fn can_evolve(&self) -> bool {
+        self.level >= MAX_WEAPON_LEVEL && !self.evolved
+    }
+
+    pub fn evolve(&mut self) {
+        if !self.can_evolve() {
+            return;
+        }
+        self.evolved = true;
+        self.name = format!("Evolved {}", self.name);
+        self.amount += 2;
+        self.projectile_lifetime *= 1.5;
+    }
+}
+
+impl Default for Weapon {
+    fn default() -> Self {
+        Weapon {
+            name: "Weapon".to_string(),
+            texture: "branding/projectile.png".to_string(),
+            projectile_speed: 200.0,
+            motion: ProjectileMotion::Straight,
+            projectile_lifetime: 5.0,
+            amount: 1,
+            spread: 0.0,
+            volley_delay: 0.0,
+            targeting: Targeting::Nearest,
+            manual_aim: false,
+            on_hit: None,
+            crit_chance: 0.0,
+            level: 1,
+            evolved: false,
+            slot: 0,
+        }
+    }
+}
+
+// A single projectile of a volley waiting for its turn to be fired
+#[derive(Component)]
+pub struct QueuedShot {
+    // The entity holding the Weapon that fired this shot
+    pub weapon: Entity,
+    // Position of the shot in its volley, used for the spread
+    pub index: u32,
+    pub delay: Timer,
+}
+
+// The angle in radians a projectile is rotated away from the aim direction, volleys fan out evenly around it
+pub fn spread_angle(index: u32, amount: u32, spread: f32) -> f32 {
+    let offset = index as f32 - (amount as f32 - 1.0) / 2.0;
+    (offset * spread).to_radians()
+}
+
+// Pick the point a weapon should fire towards, enemies are given as (position, health)
+// Returns None when there is nothing to aim at, like when there are no enemies or no cursor
+pub fn pick_target(
+    targeting: Targeting,
+    player_position: Vec2,
+    facing: Vec2,
+    cursor: Option<Vec2>,
+    enemies: &[(Vec2, i32)],
+    rng: &mut impl Rng,
+) -> Option<Vec2> {
+    match targeting {
+        Targeting::Nearest => enemies
+            .iter()
+            .min_by(|a, b| {
+                a.0.distance(player_position)
+                    .total_cmp(&b.0.distance(player_position))
+            })
+            .map(|enemy| enemy.0),
+        Targeting::Random => enemies
+            .choose(rng)
+            .map(|enemy| enemy.0),
+        Targeting::Strongest => enemies.iter().max_by_key(|enemy| enemy.1).map(|enemy| enemy.0),
+        Targeting::LowestHealth => enemies.iter().min_by_key(|enemy| enemy.1).map(|enemy| enemy.0),
+        Targeting::Facing => Some(player_position + facing),
+        Targeting::MouseCursor => cursor,
+    }
+}
+
+// The starting wand, fires a straight projectile at the closest enemy
+pub fn magic_wand() -> Weapon {
+    Weapon {
+        name: "Magic Wand".to_string(),
+        projectile_speed: 200.0,
+        motion: ProjectileMotion::Straight,
+        projectile_lifetime: 5.0,
+        manual_aim: true,
+        ..default()
+    }
+}
+
+// Throws knives in the direction the player is facing, one after another
+pub fn knife() -> Weapon {
+    Weapon {
+        name: "Knife".to_string(),
+        projectile_speed: 350.0,
+        projectile_lifetime: 2.0,
+        amount: 3,
+        volley_delay: 0.1,
+        targeting: Targeting::Facing,
+        manual_aim: true,
+        // Knives are aimed at weak spots, one in five lands a critical hit
+        crit_chance: 0.2,
+        // Every knife adds a stack of poison
+        on_hit: Some(StatusEffect {
+            kind: StatusKind::Poison,
+            duration: 3.0,
+            potency: 1.0,
+        }),
+        ..default()
+    }
+}
+
+// Fires a fan of projectiles at random enemies
+pub fn fire_wand() -> Weapon {
+    Weapon {
+        name: "Fire Wand".to_string(),
+        projectile_speed: 150.0,
+        projectile_lifetime: 4.0,
+        amount: 3,
+        spread: 15.0,
+        targeting: Targeting::Random,
+        manual_aim: true,
+        on_hit: Some(StatusEffect {
+            kind: StatusKind::Burn,
+            duration: 2.0,
+            potency: 1.0,
+        }),
+        ..default()
+    }
+}
+
+// Thrown outwards, slows down and then flies back to the player
+pub fn boomerang() -> Weapon {
+    Weapon {
+        name: "Boomerang".to_string(),
+        projectile_speed: 300.0,
+        motion: ProjectileMotion::Boomerang { deceleration: 300.0 },
+        projectile_lifetime: 6.0,
+        targeting: Targeting::Strongest,
+        on_hit: Some(StatusEffect {
+            kind: StatusKind::Weaken,
+            duration: 3.0,
+            potency: 1.5,
+        }),
+        ..default()
+    }
+}
+
+// Slowly turns towards the closest enemy
+pub fn homing_missile() -> Weapon {
+    Weapon {
+        name: "Homing Missile".to_string(),
+        projectile_speed: 150.0,
+        motion: ProjectileMotion::Homing { turn_rate: 3.0 },
+        projectile_lifetime: 6.0,
+        targeting: Targeting::LowestHealth,
+        on_hit: Some(StatusEffect {
+            kind: StatusKind::Freeze,
+            duration: 1.0,
+            potency: 1.0,
+        }),
+        ..default()
+    }
+}
+
+// Wiggles left and right around the line towards the target
+pub fn snake_staff() -> Weapon {
+    Weapon {
+        name: "Snake Staff".to_string(),
+        projectile_speed: 200.0,
+        motion: ProjectileMotion::SineWave {
+            amplitude: 30.0,
+            frequency: 2.0,
+        },
+        projectile_lifetime: 5.0,
+        ..default()
+    }
+}
+
+// Spirals outwards from where it was fired
+pub fn spiral_tome() -> Weapon {
+    Weapon {
+        name: "Spiral Tome".to_string(),
+        projectile_speed: 80.0,
+        motion: ProjectileMotion::Spiral { angular_speed: 4.0 },
+        projectile_lifetime: 4.0,
+        amount: 4,
+        spread: 90.0,
+        targeting: Targeting::Facing,
+        ..default()
+    }
+}
+
+// Lobbed upwards in an arc, falls back down with gravity
+pub fn axe() -> Weapon {
+    Weapon {
+        name: "Axe".to_string(),
+        projectile_speed: 250.0,
+        motion: ProjectileMotion::Gravity { gravity: 400.0 },
+        projectile_lifetime: 3.0,
+        amount: 2,
+        spread: 20.0,
+        manual_aim: true,
+        ..default()
+    }
+}
+
+// Fires wherever the mouse cursor is pointing
+pub fn cursor_wand() -> Weapon {
+    Weapon {
+        name: "Cursor Wand".to_string(),
+        projectile_speed: 250.0,
+        projectile_lifetime: 3.0,
+        targeting: Targeting::MouseCursor,
+        ..default()
+    }
+}
+
+// Every weapon the player can own, with the seconds between its volleys
+// The player starts with a few of them, the others are found in treasure chests
+pub fn weapon_pool() -> Vec<(Weapon, f32)> {
+    vec![
+        (magic_wand(), 4.0),
+        (boomerang(), 3.0),
+        (knife(), 2.0),
+        (fire_wand(), 3.0),
+        (homing_missile(), 4.0),
+        (snake_staff(), 3.0),
+        (spiral_tome(), 4.0),
+        (axe(), 3.0),
+        (cursor_wand(), 2.0),
+    ]
+}
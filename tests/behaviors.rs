@@ -0,0 +1,43 @@
+// Fleeing enemies come into view before they run off with their loot
+
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use rust_survivors::behavior::{self, EnemyBehavior, FLEE_DISTANCE};
+use rust_survivors::enemy::EnemyKind;
+use rust_survivors::pathfinding::FlowField;
+use rust_survivors::player::Player;
+use rust_survivors::status::StatusEffects;
+use std::time::Duration;
+
+#[test]
+fn goblins_approach_before_fleeing() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(0.1)))
+        .init_resource::<FlowField>()
+        .add_systems(Update, behavior::flee_player);
+    app.world.spawn((Transform::default(), Player::default()));
+    let goblin = app
+        .world
+        .spawn((
+            Transform::from_xyz(800.0, 0.0, 0.0),
+            EnemyKind::Goblin.stats(),
+            StatusEffects::default(),
+            EnemyKind::Goblin.behavior(),
+        ))
+        .id();
+    let distance = |app: &App| app.world.get::<Transform>(goblin).unwrap().translation.x;
+    let mut closest = f32::MAX;
+    for _ in 0..100 {
+        app.update();
+        closest = closest.min(distance(&app));
+    }
+    assert!(closest <= FLEE_DISTANCE, "the goblin stayed {} away", closest);
+    let Some(EnemyBehavior::Flee { escape, spotted }) = app.world.get::<EnemyBehavior>(goblin) else {
+        panic!("the goblin escaped while it was still approaching");
+    };
+    assert!(*spotted);
+    // The escape timer only started once the goblin was close
+    assert!(escape.elapsed_secs() < 10.0);
+    assert!(distance(&app) > closest);
+}
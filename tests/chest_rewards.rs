@@ -0,0 +1,144 @@
+// Chest reward tests, using a seeded rng like the loot table tests so the results are deterministic
+
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rust_survivors::chest::{reward_count, roll_rewards, ChestReward, OwnedWeapon};
+use rust_survivors::weapon::{self, MAX_WEAPONS, MAX_WEAPON_LEVEL};
+use std::collections::HashMap;
+
+const ROLLS: usize = 100_000;
+
+// How often a chest holds 1, 3 and 5 rewards, as a fraction of all rolls
+fn count_distribution(luck: f32, seed: u64) -> HashMap<usize, f32> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut counts = HashMap::new();
+    for _ in 0..ROLLS {
+        *counts.entry(reward_count(luck, &mut rng)).or_insert(0) += 1;
+    }
+    counts
+        .into_iter()
+        .map(|(count, times)| (count, times as f32 / ROLLS as f32))
+        .collect()
+}
+
+fn weapon(index: u32, level: u32, evolved: bool) -> OwnedWeapon {
+    OwnedWeapon {
+        entity: Entity::from_raw(index),
+        name: format!("Weapon {}", index),
+        level,
+        evolved,
+    }
+}
+
+#[test]
+fn chests_hold_one_three_or_five_rewards() {
+    let counts = count_distribution(1.0, 42);
+    assert_eq!(counts.len(), 3);
+    assert!((counts[&1] - 0.70).abs() < 0.01);
+    assert!((counts[&3] - 0.25).abs() < 0.01);
+    assert!((counts[&5] - 0.05).abs() < 0.01);
+}
+
+#[test]
+fn luck_makes_bigger_chests_more_likely() {
+    // Doubling luck doubles the weights of 3 and 5, so the total goes from 100 to 130
+    let counts = count_distribution(2.0, 42);
+    assert!((counts[&1] - 70.0 / 130.0).abs() < 0.01);
+    assert!((counts[&3] - 50.0 / 130.0).abs() < 0.01);
+    assert!((counts[&5] - 10.0 / 130.0).abs() < 0.01);
+}
+
+#[test]
+fn maxed_weapons_evolve_first() {
+    let weapons = [weapon(0, 1, false), weapon(1, MAX_WEAPON_LEVEL, false)];
+    for seed in 0..100 {
+        let rewards = roll_rewards(1.0, &weapons, &[], &mut StdRng::seed_from_u64(seed));
+        assert_eq!(
+            rewards[0],
+            ChestReward::Evolution {
+                weapon: Entity::from_raw(1),
+                name: "Weapon 1".to_string()
+            }
+        );
+        // A weapon only evolves once per chest
+        let evolutions = rewards
+            .iter()
+            .filter(|reward| matches!(reward, ChestReward::Evolution { .. }))
+            .count();
+        assert_eq!(evolutions, 1);
+    }
+}
+
+#[test]
+fn level_ups_never_go_past_the_max() {
+    let weapons = [weapon(0, MAX_WEAPON_LEVEL - 1, true)];
+    for seed in 0..100 {
+        let rewards = roll_rewards(2.0, &weapons, &[], &mut StdRng::seed_from_u64(seed));
+        for (index, reward) in rewards.iter().enumerate() {
+            match reward {
+                ChestReward::LevelUp { level, .. } => {
+                    assert_eq!(index, 0);
+                    assert_eq!(*level, MAX_WEAPON_LEVEL);
+                }
+                ChestReward::Gold(amount) => assert!((10..=50).contains(amount)),
+                ChestReward::Evolution { .. } => panic!("an evolved weapon evolved again"),
+                ChestReward::NewWeapon { .. } => panic!("there are no new weapons to find"),
+            }
+        }
+    }
+}
+
+#[test]
+fn chests_give_gold_once_everything_is_maxed() {
+    let weapons = [weapon(0, MAX_WEAPON_LEVEL, true), weapon(1, MAX_WEAPON_LEVEL, true)];
+    let rewards = roll_rewards(1.0, &weapons, &[], &mut StdRng::seed_from_u64(5));
+    assert!(!rewards.is_empty());
+    assert!(rewards.iter().all(|reward| matches!(reward, ChestReward::Gold(_))));
+}
+
+#[test]
+fn new_weapons_are_found_until_the_player_carries_the_most() {
+    let new_weapons = ["Axe".to_string(), "Fire Wand".to_string(), "Snake Staff".to_string()];
+    // With everything maxed out, new weapons come before gold and none of them twice
+    let weapons = [weapon(0, MAX_WEAPON_LEVEL, true)];
+    for seed in 0..100 {
+        let rewards = roll_rewards(2.0, &weapons, &new_weapons, &mut StdRng::seed_from_u64(seed));
+        let mut found: Vec<&String> = rewards
+            .iter()
+            .filter_map(|reward| match reward {
+                ChestReward::NewWeapon { name } => Some(name),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(found.len(), rewards.len().min(new_weapons.len()));
+        found.sort();
+        found.dedup();
+        assert_eq!(found.len(), rewards.len().min(new_weapons.len()));
+    }
+    // A player carrying the most weapons doesn't find any more
+    let full: Vec<OwnedWeapon> = (0..MAX_WEAPONS as u32).map(|index| weapon(index, 1, false)).collect();
+    for seed in 0..100 {
+        let rewards = roll_rewards(2.0, &full, &new_weapons, &mut StdRng::seed_from_u64(seed));
+        assert!(!rewards.iter().any(|reward| matches!(reward, ChestReward::NewWeapon { .. })));
+    }
+}
+
+#[test]
+fn every_weapon_can_be_found() {
+    let names: Vec<String> = weapon::weapon_pool().into_iter().map(|(weapon, _)| weapon.name).collect();
+    let weapons = [
+        weapon::magic_wand(),
+        weapon::knife(),
+        weapon::boomerang(),
+        weapon::fire_wand(),
+        weapon::homing_missile(),
+        weapon::snake_staff(),
+        weapon::spiral_tome(),
+        weapon::axe(),
+        weapon::cursor_wand(),
+    ];
+    for name in weapons.iter().map(|weapon| weapon.name.clone()) {
+        assert!(names.contains(&name), "{} is not in the weapon pool", name);
+    }
+}
@@ -0,0 +1,90 @@
+// Hits on the same enemy add up into one number, and there is a limit to how many numbers are shown
+
+use bevy::prelude::*;
+use rust_survivors::damage::{DamageDealtEvent, DamageKind};
+use rust_survivors::damage_number::{self, DamageNumber, MAX_DAMAGE_NUMBERS, MERGE_WINDOW};
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_event::<DamageDealtEvent>()
+        .add_systems(Update, damage_number::spawn_damage_numbers);
+    app
+}
+
+fn hit(target: Entity, amount: i32, kind: DamageKind, critical: bool) -> DamageDealtEvent {
+    DamageDealtEvent {
+        target,
+        position: Vec2::ZERO,
+        amount,
+        kind,
+        critical,
+    }
+}
+
+fn numbers(app: &mut App) -> Vec<(i32, bool)> {
+    let mut numbers: Vec<(i32, bool)> = app
+        .world
+        .query::<&DamageNumber>()
+        .iter(&app.world)
+        .map(|number| (number.amount, number.critical))
+        .collect();
+    numbers.sort();
+    numbers
+}
+
+#[test]
+fn hits_on_the_same_enemy_add_up() {
+    let mut app = app();
+    let enemy = app.world.spawn_empty().id();
+    app.world.send_event(hit(enemy, 1, DamageKind::Physical, false));
+    app.world.send_event(hit(enemy, 2, DamageKind::Physical, true));
+    app.update();
+    assert_eq!(numbers(&mut app), vec![(3, true)]);
+
+    // A later hit still adds to the number while it is fresh
+    app.world.send_event(hit(enemy, 4, DamageKind::Physical, false));
+    app.update();
+    assert_eq!(numbers(&mut app), vec![(7, true)]);
+}
+
+#[test]
+fn different_kinds_and_old_numbers_get_their_own_number() {
+    let mut app = app();
+    let enemy = app.world.spawn_empty().id();
+    app.world.send_event(hit(enemy, 1, DamageKind::Physical, false));
+    app.world.send_event(hit(enemy, 2, DamageKind::Poison, false));
+    app.update();
+    assert_eq!(numbers(&mut app), vec![(1, false), (2, false)]);
+
+    for mut number in app.world.query::<&mut DamageNumber>().iter_mut(&mut app.world) {
+        number.age = MERGE_WINDOW;
+    }
+    app.world.send_event(hit(enemy, 5, DamageKind::Physical, false));
+    app.update();
+    assert_eq!(numbers(&mut app), vec![(1, false), (2, false), (5, false)]);
+}
+
+#[test]
+fn numbers_are_capped() {
+    let mut app = app();
+    for _ in 0..MAX_DAMAGE_NUMBERS + 10 {
+        let enemy = app.world.spawn_empty().id();
+        app.world.send_event(hit(enemy, 1, DamageKind::Fire, false));
+    }
+    app.update();
+    assert_eq!(numbers(&mut app).len(), MAX_DAMAGE_NUMBERS);
+}
+
+#[test]
+fn critical_numbers_are_bigger_and_fade_out() {
+    let mut number = DamageNumber::new(Entity::PLACEHOLDER, DamageKind::Holy, 2, false);
+    let normal_size = number.font_size();
+    assert_eq!(number.label(), "2");
+    assert_eq!(number.alpha(), 1.0);
+    number.add(2, true);
+    assert_eq!(number.label(), "4!");
+    assert!(number.font_size() > normal_size);
+    number.age = damage_number::DAMAGE_NUMBER_LIFETIME;
+    assert_eq!(number.alpha(), 0.0);
+}
@@ -0,0 +1,95 @@
+// Elite chance tests, the chance grows with the time on the run clock and is capped
+// Shielded elites ignore normal hits, but not the bomb pickup
+
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rust_survivors::camera;
+use rust_survivors::damage::{self, DamageDealtEvent, DamageEvent, EnemyDeathEvent};
+use rust_survivors::elite::{self, elite_chance, roll_elite, EliteAffix, ELITE_SHIELD_HITS};
+use rust_survivors::enemy::EnemyKind;
+use rust_survivors::game_state::GameState;
+use rust_survivors::pickup::{self, Pickup, PickupKind};
+use rust_survivors::player::Player;
+use rust_survivors::prop::PropDestroyedEvent;
+use rust_survivors::stage::RunClock;
+use rust_survivors::status::StatusEvent;
+
+const ROLLS: usize = 100_000;
+
+// How often an enemy became an elite, as a fraction of all rolls
+fn elite_rate(elapsed: f32, difficulty: f32) -> f32 {
+    let clock = RunClock { elapsed };
+    let mut rng = StdRng::seed_from_u64(9);
+    let elites = (0..ROLLS)
+        .filter(|_| roll_elite(&clock, difficulty, &mut rng).is_some())
+        .count();
+    elites as f32 / ROLLS as f32
+}
+
+#[test]
+fn chance_grows_every_minute_and_is_capped() {
+    assert!((elite_chance(0.0, 1.0) - 0.02).abs() < 1e-6);
+    assert!((elite_chance(600.0, 1.0) - 0.12).abs() < 1e-6);
+    assert!((elite_chance(600.0, 2.0) - 0.24).abs() < 1e-6);
+    assert_eq!(elite_chance(60.0 * 60.0, 1.0), 0.25);
+}
+
+#[test]
+fn a_new_run_starts_with_the_base_chance() {
+    assert!((elite_rate(0.0, 1.0) - 0.02).abs() < 0.005);
+    assert!((elite_rate(600.0, 1.0) - 0.12).abs() < 0.005);
+}
+
+// A shielded elite in view of the camera, with the player standing next to it
+fn shielded_elite_app() -> (App, Entity) {
+    let mut app = App::new();
+    app.add_state::<GameState>()
+        .add_event::<DamageEvent>()
+        .add_event::<DamageDealtEvent>()
+        .add_event::<EnemyDeathEvent>()
+        .add_event::<PropDestroyedEvent>()
+        .add_event::<StatusEvent>()
+        .add_systems(Update, (pickup::pickup_collision, damage::apply_damage).chain());
+    app.world.spawn((Transform::default(), Player::default()));
+    let projection = OrthographicProjection {
+        area: Rect::from_center_size(Vec2::ZERO, Vec2::new(1280.0, 720.0)),
+        ..default()
+    };
+    app.world.spawn((projection, Transform::default(), camera::Camera));
+    let mut enemy = EnemyKind::Skeleton.stats();
+    let shield = elite::promote(&mut enemy, EliteAffix::Shielded);
+    let elite = app.world.spawn((Transform::from_xyz(200.0, 0.0, 0.0), enemy, shield)).id();
+    (app, elite)
+}
+
+#[test]
+fn shields_block_normal_hits() {
+    let (mut app, elite) = shielded_elite_app();
+    for _ in 0..ELITE_SHIELD_HITS {
+        app.world.send_event(DamageEvent {
+            target: elite,
+            amount: 1000,
+            kind: damage::DamageKind::Physical,
+            critical: false,
+            lethal: false,
+        });
+    }
+    app.update();
+    assert!(app.world.get_entity(elite).is_some());
+}
+
+#[test]
+fn a_bomb_kills_a_shielded_elite() {
+    let (mut app, elite) = shielded_elite_app();
+    app.world.spawn((
+        Transform::default(),
+        Pickup {
+            kind: PickupKind::Bomb,
+            size: Vec2::splat(20.0),
+        },
+    ));
+    app.update();
+    assert!(app.world.get_entity(elite).is_none());
+    assert_eq!(app.world.resource::<Events<EnemyDeathEvent>>().len(), 1);
+}
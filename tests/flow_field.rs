@@ -0,0 +1,128 @@
+// Flow field tests on a small grid, checking that the directions lead to the target and around obstacles
+
+use bevy::prelude::*;
+use rust_survivors::camera;
+use rust_survivors::obstacle::Obstacle;
+use rust_survivors::pathfinding::{self, FlowField};
+use rust_survivors::player::Player;
+
+const CELL: f32 = 10.0;
+
+// Follow the field from start until it stops giving directions, returning every cell visited
+fn walk(field: &FlowField, start: Vec2) -> Vec<IVec2> {
+    let mut position = start;
+    let mut cells = vec![field.cell(position)];
+    for _ in 0..100 {
+        let Some(direction) = field.direction(position) else {
+            break;
+        };
+        let next = field.cell(position) + direction.round().as_ivec2();
+        position = field.cell_center(next);
+        cells.push(next);
+    }
+    cells
+}
+
+#[test]
+fn open_field_points_at_target() {
+    let mut field = FlowField::new(CELL, 5);
+    field.set_obstacles(IVec2::ZERO, &[]);
+    field.compute(Vec2::new(5.0, 5.0));
+    assert_eq!(field.direction(Vec2::new(45.0, 5.0)), Some(Vec2::NEG_X));
+    assert_eq!(field.direction(Vec2::new(5.0, -35.0)), Some(Vec2::Y));
+    let diagonal = field.direction(Vec2::new(35.0, 35.0)).unwrap();
+    assert!((diagonal - Vec2::new(-1.0, -1.0).normalize()).length() < 0.001);
+}
+
+#[test]
+fn target_cell_and_outside_have_no_direction() {
+    let mut field = FlowField::new(CELL, 5);
+    field.set_obstacles(IVec2::ZERO, &[]);
+    field.compute(Vec2::new(5.0, 5.0));
+    assert_eq!(field.direction(Vec2::new(5.0, 5.0)), None);
+    assert_eq!(field.direction(Vec2::new(500.0, 5.0)), None);
+}
+
+#[test]
+fn paths_go_around_walls() {
+    let mut field = FlowField::new(CELL, 5);
+    // A wall between x 20 and 30, from y -30 up to 40, with the target on its left
+    let wall = Rect::new(20.0, -30.0, 30.0, 40.0);
+    field.set_obstacles(IVec2::ZERO, &[wall]);
+    field.compute(Vec2::new(5.0, 5.0));
+    let path = walk(&field, Vec2::new(45.0, 5.0));
+    assert_eq!(*path.last().unwrap(), IVec2::ZERO);
+    assert!(path.iter().all(|cell| !field.is_blocked(*cell)));
+}
+
+#[test]
+fn enclosed_cells_are_unreachable() {
+    let mut field = FlowField::new(CELL, 5);
+    // A box around the cell at (3, 3)
+    let walls = [
+        Rect::new(20.0, 20.0, 50.0, 30.0),
+        Rect::new(20.0, 40.0, 50.0, 50.0),
+        Rect::new(20.0, 30.0, 30.0, 40.0),
+        Rect::new(40.0, 30.0, 50.0, 40.0),
+    ];
+    field.set_obstacles(IVec2::ZERO, &walls);
+    field.compute(Vec2::new(5.0, 5.0));
+    assert!(!field.is_blocked(IVec2::new(3, 3)));
+    assert_eq!(field.direction(Vec2::new(35.0, 35.0)), None);
+}
+
+#[test]
+fn search_spread_over_frames_matches_full_compute() {
+    let wall = Rect::new(20.0, -30.0, 30.0, 40.0);
+    let mut full = FlowField::new(CELL, 5);
+    full.set_obstacles(IVec2::ZERO, &[wall]);
+    full.compute(Vec2::new(5.0, 5.0));
+
+    let mut field = FlowField::new(CELL, 5);
+    field.set_obstacles(IVec2::ZERO, &[wall]);
+    field.compute(Vec2::new(-35.0, -35.0));
+    field.start(Vec2::new(5.0, 5.0));
+    let mut frames = 1;
+    while !field.step(10) {
+        // Until the search is done enemies keep following the old field
+        assert_eq!(field.target, Some(IVec2::new(-4, -4)));
+        assert!(field.is_searching());
+        frames += 1;
+    }
+    // 121 cells to search and 121 to point, 10 per frame
+    assert!(frames > 20);
+    assert_eq!(field.target, Some(IVec2::ZERO));
+    assert_eq!(field.distances, full.distances);
+    assert_eq!(field.directions, full.directions);
+}
+
+#[test]
+fn removed_obstacles_only_rebuild_once() {
+    let mut app = App::new();
+    app.insert_resource(FlowField::new(CELL, 5))
+        .add_systems(Update, pathfinding::update_flow_field);
+    app.world.spawn((Transform::default(), Player::default()));
+    app.world.spawn((Transform::default(), camera::Camera));
+    let obstacle = || Obstacle { size: Vec2::splat(CELL) };
+    let removed = app.world.spawn((Transform::from_xyz(-25.0, 5.0, 0.0), obstacle())).id();
+    let moved = app.world.spawn((Transform::from_xyz(25.0, 5.0, 0.0), obstacle())).id();
+    app.update();
+
+    // One obstacle is removed and another one moves in the same frame
+    app.world.despawn(removed);
+    app.world.get_mut::<Transform>(moved).unwrap().translation.y = 15.0;
+    app.update();
+    assert!(app.world.resource::<FlowField>().is_blocked(IVec2::new(2, 1)));
+
+    // Nothing changed since, so sneaking the obstacle somewhere else isn't picked up by a stale removal
+    app.world
+        .get_mut::<Transform>(moved)
+        .unwrap()
+        .bypass_change_detection()
+        .translation
+        .y = 35.0;
+    app.update();
+    let field = app.world.resource::<FlowField>();
+    assert!(field.is_blocked(IVec2::new(2, 1)));
+    assert!(!field.is_blocked(IVec2::new(2, 3)));
+}
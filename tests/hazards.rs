@@ -0,0 +1,83 @@
+// Hazard timing tests, stepped with a fixed time step: the fuse delays the first hit, then the hazard hits every tick
+
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use rust_survivors::damage::DamageEvent;
+use rust_survivors::enemy::EnemyKind;
+use rust_survivors::hazard::{self, Hazard, HazardWeapon};
+use rust_survivors::status::StatusEvent;
+use std::time::Duration;
+
+const STEP: f32 = 0.25;
+
+#[derive(Resource, Default)]
+struct Hits(usize);
+
+fn count_hits(mut damage_events: EventReader<DamageEvent>, mut hits: ResMut<Hits>) {
+    hits.0 += damage_events.read().count();
+}
+
+// An app with the hazard of the given weapon lying on top of a single enemy
+fn app(weapon: HazardWeapon) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(STEP)))
+        .init_resource::<Hits>()
+        .add_event::<DamageEvent>()
+        .add_event::<StatusEvent>()
+        .add_systems(Update, (hazard::update_hazards, count_hits).chain());
+    app.world.spawn((Transform::default(), EnemyKind::Skeleton.stats()));
+    app.world.spawn((
+        Transform::default(),
+        Hazard {
+            radius: weapon.radius,
+            damage: weapon.damage,
+            damage_kind: weapon.damage_kind,
+            fuse: Timer::from_seconds(weapon.fuse, TimerMode::Once),
+            tick: Timer::from_seconds(weapon.tick_rate, TimerMode::Repeating),
+            lifetime: Timer::from_seconds(weapon.duration, TimerMode::Once),
+            on_hit: weapon.on_hit,
+        },
+    ));
+    // The first update only starts the clock
+    app.update();
+    app
+}
+
+fn run(app: &mut App, seconds: f32) {
+    for _ in 0..(seconds / STEP).round() as usize {
+        app.update();
+    }
+}
+
+fn hazard_count(app: &mut App) -> usize {
+    app.world.query::<&Hazard>().iter(&app.world).count()
+}
+
+#[test]
+fn bomb_hits_once_after_its_fuse() {
+    let bomb = hazard::bomb();
+    let fuse = bomb.fuse;
+    let mut app = app(bomb);
+    run(&mut app, fuse - STEP);
+    assert_eq!(app.world.resource::<Hits>().0, 0);
+    run(&mut app, STEP);
+    assert_eq!(app.world.resource::<Hits>().0, 1);
+    assert_eq!(hazard_count(&mut app), 0);
+    run(&mut app, 2.0);
+    assert_eq!(app.world.resource::<Hits>().0, 1);
+}
+
+#[test]
+fn puddle_ticks_over_its_duration() {
+    let puddle = hazard::holy_water();
+    // One hit right away, then one every tick, the last one lands on the frame the lifetime runs out
+    let expected = 1 + (puddle.duration / puddle.tick_rate) as usize;
+    let duration = puddle.duration;
+    let mut app = app(puddle);
+    run(&mut app, duration);
+    assert_eq!(app.world.resource::<Hits>().0, expected);
+    assert_eq!(hazard_count(&mut app), 0);
+    run(&mut app, 2.0);
+    assert_eq!(app.world.resource::<Hits>().0, expected);
+}
@@ -0,0 +1,255 @@
+// Gameplay systems run without any UI entities, and the HUD labels follow the player when they do exist
+
+use bevy::prelude::*;
+use rust_survivors::enemy::EnemyKind;
+use rust_survivors::experience_item::ExperienceItem;
+use rust_survivors::player::{self, Player};
+use rust_survivors::status::{StatusEffect, StatusEffects, StatusKind};
+use rust_survivors::hazard;
+use rust_survivors::obstacle::MapBounds;
+use rust_survivors::progress::Progress;
+use rust_survivors::game_state::GameState;
+use rust_survivors::run::{self, GameOverScreen, RunStats};
+use rust_survivors::stage::{fallback_stage, RunClock, SelectedStage};
+use rust_survivors::ui::{self, ExperienceText, GoldText, HealthText, KillText, LevelText, RunTimerText};
+use rust_survivors::weapon::{self, NextSlot};
+
+// An app with the player, an enemy standing on top of them and an experience item worth a level
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins).add_systems(
+        Update,
+        (player::player_collision, player::experience_collision).chain(),
+    );
+    app.world.spawn((Transform::default(), Player::default(), StatusEffects::default()));
+    app.world.spawn((Transform::default(), EnemyKind::Skeleton.stats()));
+    app.world.spawn((
+        Transform::default(),
+        ExperienceItem {
+            experience: 10,
+            size: Vec2::splat(10.0),
+            attracted: false,
+            speed: 0.0,
+        },
+    ));
+    app
+}
+
+fn player_state(app: &mut App) -> (i32, i32) {
+    let player = app.world.query::<&Player>().single(&app.world);
+    (player.health, player.level)
+}
+
+#[test]
+fn gameplay_runs_without_ui() {
+    let mut app = app();
+    app.update();
+    assert_eq!(player_state(&mut app), (99, 2));
+}
+
+#[test]
+fn labels_show_the_state_after_the_hit() {
+    let mut app = app();
+    app.add_systems(
+        PostUpdate,
+        (ui::update_health_text, ui::update_experience_text, ui::update_level_text),
+    );
+    let health = app.world.spawn((Text::from_section("", default()), HealthText)).id();
+    let experience = app.world.spawn((Text::from_section("", default()), ExperienceText)).id();
+    let level = app.world.spawn((Text::from_section("", default()), LevelText)).id();
+    app.update();
+    let label = |app: &App, entity| app.world.get::<Text>(entity).unwrap().sections[0].value.clone();
+    assert_eq!(label(&app, health), "Health: 99");
+    assert_eq!(label(&app, experience), "Experience: 0");
+    assert_eq!(label(&app, level), "Level: 2");
+}
+
+#[test]
+fn kill_and_gold_labels_follow_the_run() {
+    let mut app = app();
+    app.insert_resource(RunStats::default())
+        .add_systems(PostUpdate, (ui::update_gold_text, ui::update_kill_text));
+    let gold = app.world.spawn((Text::from_section("", default()), GoldText)).id();
+    let kills = app.world.spawn((Text::from_section("", default()), KillText)).id();
+    app.world.query::<&mut Player>().single_mut(&mut app.world).gold = 25;
+    app.world.resource_mut::<RunStats>().kills = 3;
+    app.update();
+    let label = |app: &App, entity| app.world.get::<Text>(entity).unwrap().sections[0].value.clone();
+    assert_eq!(label(&app, gold), "Gold: 25");
+    assert_eq!(label(&app, kills), "Kills: 3");
+}
+
+#[test]
+fn item_bar_lists_items_in_pickup_order() {
+    let mut next_slot = NextSlot::default();
+    let wand = weapon::Weapon { slot: next_slot.take(), ..weapon::magic_wand() };
+    let water = hazard::HazardWeapon { slot: next_slot.take(), ..hazard::holy_water() };
+    let knife = weapon::Weapon { slot: next_slot.take(), level: 3, ..weapon::knife() };
+    let bomb = hazard::HazardWeapon { slot: next_slot.take(), ..hazard::bomb() };
+    // The weapons come in any order, like they do from a query, and are sorted by when they were picked up
+    let weapons = [knife, wand];
+    let hazard_weapons = [bomb, water];
+    let slots = ui::item_slots(weapons.iter(), hazard_weapons.iter());
+    let names: Vec<&str> = slots.iter().map(|slot| slot.name.as_str()).collect();
+    assert_eq!(names, ["Magic Wand", "Holy Water", "Knife", "Bomb"]);
+    assert_eq!(slots[2].label(), "Lv 3");
+    assert_eq!(slots[2].texture.as_deref(), Some(weapons[0].texture.as_str()));
+    // Hazard weapons have no texture or level, their icon is a solid square in their color
+    assert_eq!(slots[3].texture, None);
+    assert_eq!(slots[3].label(), "");
+    assert_eq!(slots[3].color.a(), 1.0);
+}
+
+#[test]
+fn item_bar_is_rebuilt_when_a_weapon_is_removed() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<Image>()
+        .add_systems(Update, ui::update_item_bar);
+    let bar = app.world.spawn((NodeBundle::default(), ui::ItemBar)).id();
+    let knife = app.world.spawn(weapon::knife()).id();
+    app.world.spawn(hazard::bomb());
+    app.update();
+    assert_eq!(app.world.get::<Children>(bar).unwrap().len(), 2);
+    app.world.despawn(knife);
+    app.update();
+    assert_eq!(app.world.get::<Children>(bar).unwrap().len(), 1);
+}
+
+#[test]
+fn the_reaper_takes_all_health_through_invulnerability() {
+    let mut app = App::new();
+    app.add_systems(Update, player::player_collision);
+    let mut statuses = StatusEffects::default();
+    statuses.apply(StatusEffect {
+        kind: StatusKind::Invulnerable,
+        duration: 10.0,
+        potency: 1.0,
+    });
+    app.world.spawn((Transform::default(), Player::default(), statuses));
+    app.world.spawn((Transform::default(), EnemyKind::Reaper.stats()));
+    app.world.spawn((Transform::default(), EnemyKind::Skeleton.stats()));
+    app.update();
+    assert_eq!(player_state(&mut app).0, 0);
+}
+
+#[test]
+fn the_run_is_over_once_the_player_has_no_health_left() {
+    let mut app = App::new();
+    app.add_state::<GameState>()
+        .insert_resource(NextState(Some(GameState::Playing)))
+        .init_resource::<RunClock>()
+        .init_resource::<RunStats>()
+        .add_systems(
+            Update,
+            (player::player_collision, run::check_player_death)
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(OnEnter(GameState::GameOver), run::open_game_over_screen);
+    app.world.spawn((Transform::default(), Player::default(), StatusEffects::default()));
+    app.update();
+    assert_eq!(*app.world.resource::<State<GameState>>().get(), GameState::Playing);
+    app.world.spawn((Transform::default(), EnemyKind::Reaper.stats()));
+    app.update();
+    app.update();
+    assert_eq!(*app.world.resource::<State<GameState>>().get(), GameState::GameOver);
+    assert_eq!(app.world.query::<&GameOverScreen>().iter(&app.world).count(), 1);
+}
+
+#[test]
+fn the_run_only_ends_once_the_reaper_can_spawn() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .insert_resource(RunClock { elapsed: 10_000.0 })
+        .insert_resource(SelectedStage(fallback_stage()))
+        .insert_resource(RunStats::default())
+        .insert_resource(Progress::default())
+        .add_systems(Update, run::end_run);
+    app.update();
+    // There is no camera to spawn the reaper next to, so the run isn't over yet
+    assert!(!app.world.resource::<RunStats>().victory);
+    assert!(app.world.resource::<Progress>().stages.is_empty());
+}
+
+#[test]
+fn labels_are_left_alone_when_nothing_changed() {
+    let mut app = app();
+    app.init_resource::<Input<KeyCode>>()
+        .init_resource::<MapBounds>()
+        .add_systems(Update, (player::sprite_movement, player::regenerate_health))
+        .add_systems(PostUpdate, ui::update_health_text);
+    let health = app.world.spawn((Text::from_section("", default()), HealthText)).id();
+    app.world.query::<&mut Player>().single_mut(&mut app.world).regen = 0.1;
+    app.update();
+    assert_eq!(app.world.get::<Text>(health).unwrap().sections[0].value, "Health: 99");
+    // Standing still while regenerating doesn't change anything the HUD shows, so the label isn't rewritten
+    app.world.get_mut::<Text>(health).unwrap().sections[0].value = "untouched".to_string();
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(app.world.get::<Text>(health).unwrap().sections[0].value, "untouched");
+
+    // Walking changes the player every frame, but the health shown is the same so the label isn't written either
+    app.world.get_mut::<Text>(health).unwrap().sections[0].value = "Health: 99".to_string();
+    app.update();
+    let written = app.world.entity(health).get_ref::<Text>().unwrap().last_changed();
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::D);
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(app.world.query::<&Player>().single(&app.world).position.0 > 0.0);
+    assert_eq!(app.world.entity(health).get_ref::<Text>().unwrap().last_changed(), written);
+}
+
+#[test]
+fn health_bar_rides_on_the_player_and_only_changes_with_health() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, TransformPlugin))
+        .init_resource::<Input<KeyCode>>()
+        .init_resource::<MapBounds>()
+        .add_systems(Update, player::sprite_movement)
+        .add_systems(PostUpdate, (ui::attach_health_bar, ui::update_health_bar));
+    let player = app
+        .world
+        .spawn((Transform::from_scale(Vec3::splat(0.5)), GlobalTransform::default(), Player::default()))
+        .id();
+    let bar = app
+        .world
+        .spawn((TransformBundle::from_transform(Transform::from_xyz(0.0, ui::HEALTH_BAR_OFFSET, 5.0)), ui::HealthBar))
+        .id();
+    let fill = app.world.spawn((Sprite::default(), ui::HealthBarFill)).id();
+    app.update();
+    assert_eq!(app.world.get::<Parent>(bar).unwrap().get(), player);
+    assert_eq!(app.world.get::<Sprite>(fill).unwrap().custom_size, Some(ui::HEALTH_BAR_SIZE));
+
+    // Walking moves the bar along with the player, at its full size, without rewriting the fill
+    app.world.get_mut::<Sprite>(fill).unwrap().color = Color::PINK;
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::D);
+    for _ in 0..3 {
+        app.update();
+    }
+    let player_x = app.world.get::<Transform>(player).unwrap().translation.x;
+    assert!(player_x > 0.0);
+    let (scale, _, translation) = app.world.get::<GlobalTransform>(bar).unwrap().to_scale_rotation_translation();
+    assert!(scale.abs_diff_eq(Vec3::ONE, 1e-5));
+    assert!(translation.abs_diff_eq(Vec3::new(player_x, ui::HEALTH_BAR_OFFSET, 5.0), 1e-3));
+    assert_eq!(app.world.get::<Sprite>(fill).unwrap().color, Color::PINK);
+}
+
+#[test]
+fn run_timer_is_only_written_when_the_second_changes() {
+    let mut app = App::new();
+    app.init_resource::<RunClock>()
+        .insert_resource(SelectedStage(fallback_stage()))
+        .add_systems(Update, ui::update_run_timer);
+    let timer = app.world.spawn((Text::from_section("", default()), RunTimerText)).id();
+    app.update();
+    let written = app.world.entity(timer).get_ref::<Text>().unwrap().last_changed();
+    app.world.resource_mut::<RunClock>().elapsed = 0.5;
+    app.update();
+    assert_eq!(app.world.entity(timer).get_ref::<Text>().unwrap().last_changed(), written);
+    app.world.resource_mut::<RunClock>().elapsed = 1.2;
+    app.update();
+    assert_eq!(app.world.get::<Text>(timer).unwrap().sections[0].value, "00:01");
+}
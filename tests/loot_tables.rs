@@ -0,0 +1,111 @@
+// Drop distribution tests for the loot tables, using a seeded rng so the results are deterministic
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rust_survivors::enemy::EnemyKind;
+use rust_survivors::loot::{
+    LootDrop, LootEntry, LootTable, LootTables, PropLootTables, LOOT_TABLES_PATH, PROP_LOOT_TABLES_PATH,
+};
+use rust_survivors::prop::PropKind;
+use std::collections::HashMap;
+
+const ROLLS: usize = 100_000;
+
+fn table() -> LootTable {
+    LootTable {
+        always: vec![LootDrop::Experience],
+        roll: vec![
+            LootEntry { drop: LootDrop::Nothing, weight: 70.0, rare: false },
+            LootEntry { drop: LootDrop::Gold(1), weight: 20.0, rare: false },
+            LootEntry { drop: LootDrop::Magnet, weight: 10.0, rare: true },
+        ],
+    }
+}
+
+// Roll the table many times and return how often every drop came up, as a fraction of all rolls
+fn distribution(table: &LootTable, luck: f32, seed: u64) -> HashMap<LootDrop, f32> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut counts = HashMap::new();
+    for _ in 0..ROLLS {
+        *counts.entry(table.roll(luck, &mut rng)).or_insert(0) += 1;
+    }
+    counts
+        .into_iter()
+        .map(|(drop, count)| (drop, count as f32 / ROLLS as f32))
+        .collect()
+}
+
+#[test]
+fn drops_follow_their_weights() {
+    let drops = distribution(&table(), 1.0, 42);
+    assert!((drops[&LootDrop::Nothing] - 0.7).abs() < 0.01);
+    assert!((drops[&LootDrop::Gold(1)] - 0.2).abs() < 0.01);
+    assert!((drops[&LootDrop::Magnet] - 0.1).abs() < 0.01);
+}
+
+#[test]
+fn luck_makes_rare_drops_more_likely() {
+    // Doubling luck doubles the rare weight, so magnets go from 10 / 100 to 20 / 110
+    let drops = distribution(&table(), 2.0, 42);
+    assert!((drops[&LootDrop::Magnet] - 20.0 / 110.0).abs() < 0.01);
+    assert!((drops[&LootDrop::Nothing] - 70.0 / 110.0).abs() < 0.01);
+}
+
+#[test]
+fn same_seed_gives_same_drops() {
+    let table = table();
+    let mut first = StdRng::seed_from_u64(7);
+    let mut second = StdRng::seed_from_u64(7);
+    for _ in 0..1000 {
+        assert_eq!(table.drops(1.0, &mut first), table.drops(1.0, &mut second));
+    }
+}
+
+#[test]
+fn always_drops_come_first() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let drops = table().drops(1.0, &mut rng);
+    assert_eq!(drops.len(), 2);
+    assert_eq!(drops[0], LootDrop::Experience);
+}
+
+#[test]
+fn empty_table_drops_nothing() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let table = LootTable { always: vec![], roll: vec![] };
+    assert_eq!(table.roll(1.0, &mut rng), LootDrop::Nothing);
+}
+
+#[test]
+fn loot_table_file_covers_every_enemy() {
+    let source = std::fs::read_to_string(LOOT_TABLES_PATH).unwrap();
+    let tables = LootTables::from_ron(&source).unwrap();
+    for kind in [
+        EnemyKind::Bat,
+        EnemyKind::Skeleton,
+        EnemyKind::Brute,
+        EnemyKind::Ghost,
+        EnemyKind::Goblin,
+    ] {
+        let table = &tables.0[&kind];
+        assert!(table.always.contains(&LootDrop::Experience), "{:?} drops no experience", kind);
+    }
+}
+
+#[test]
+fn kinds_without_a_table_drop_nothing() {
+    let source = std::fs::read_to_string(LOOT_TABLES_PATH).unwrap();
+    let tables = LootTables::from_ron(&source).unwrap();
+    assert!(!tables.0.contains_key(&EnemyKind::Reaper));
+    let mut rng = StdRng::seed_from_u64(1);
+    assert_eq!(tables.table(EnemyKind::Reaper).drops(1.0, &mut rng), [LootDrop::Nothing]);
+}
+
+#[test]
+fn prop_loot_table_file_covers_every_breakable_prop() {
+    let source = std::fs::read_to_string(PROP_LOOT_TABLES_PATH).unwrap();
+    let tables = PropLootTables::from_ron(&source).unwrap();
+    for kind in [PropKind::Brazier, PropKind::Crate, PropKind::Statue, PropKind::Candle] {
+        assert_eq!(kind.health().is_some(), tables.0.contains_key(&kind), "{:?}", kind);
+    }
+}
@@ -0,0 +1,80 @@
+// Tests for reading Tiled maps, checking the coordinate conversion and the objects every map file contains
+
+use bevy::prelude::*;
+use rust_survivors::ground::Biome;
+use rust_survivors::map::{MapDefinition, MapObjectKind};
+use rust_survivors::obstacle::ObstacleKind;
+use rust_survivors::prop::PropKind;
+
+// A 200x100 map with one object
+fn map(object: &str) -> String {
+    format!(
+        r#"{{
+            "width": 4, "height": 2, "tilewidth": 50, "tileheight": 50,
+            "properties": [{{ "name": "bounded", "type": "bool", "value": true }}],
+            "layers": [
+                {{ "type": "tilelayer", "data": [] }},
+                {{ "type": "objectgroup", "objects": [{}] }}
+            ]
+        }}"#,
+        object
+    )
+}
+
+#[test]
+fn objects_are_centered_on_the_origin() {
+    // A wall in the top left corner of the map
+    let source = map(r#"{ "type": "obstacle", "x": 0, "y": 0, "width": 50, "height": 20,
+        "properties": [{ "name": "kind", "type": "string", "value": "Wall" }] }"#);
+    let map = MapDefinition::from_json(&source).unwrap();
+    assert_eq!(map.size, Vec2::new(200.0, 100.0));
+    assert_eq!(map.objects[0].kind, MapObjectKind::Obstacle(ObstacleKind::Wall));
+    assert_eq!(map.objects[0].position, Vec2::new(-75.0, 40.0));
+    assert_eq!(map.objects[0].size, Vec2::new(50.0, 20.0));
+}
+
+#[test]
+fn map_properties_have_defaults() {
+    let source = map(r#"{ "type": "spawn_zone", "x": 0, "y": 0, "width": 10, "height": 10 }"#);
+    let map = MapDefinition::from_json(&source).unwrap();
+    assert!(map.bounded);
+    assert_eq!(map.biome, Biome::Forest);
+    assert_eq!(map.bounds().0, Some(Rect::new(-100.0, -50.0, 100.0, 50.0)));
+}
+
+#[test]
+fn points_get_the_size_of_their_kind() {
+    let source = map(r#"{ "class": "prop", "x": 100, "y": 50, "point": true,
+        "properties": [{ "name": "kind", "type": "string", "value": "Crate" }] }"#);
+    let map = MapDefinition::from_json(&source).unwrap();
+    assert_eq!(map.objects[0].kind, MapObjectKind::Prop(PropKind::Crate));
+    assert_eq!(map.objects[0].position, Vec2::ZERO);
+    assert_eq!(map.objects[0].size, PropKind::Crate.size());
+}
+
+#[test]
+fn light_colors_are_read_as_argb() {
+    let source = map(r##"{ "type": "light", "x": 0, "y": 0, "point": true,
+        "properties": [{ "name": "color", "type": "color", "value": "#80ff0000" }] }"##);
+    let map = MapDefinition::from_json(&source).unwrap();
+    let MapObjectKind::Light { color, .. } = map.objects[0].kind else {
+        panic!("not a light");
+    };
+    assert_eq!(color.r(), 1.0);
+    assert!((color.a() - 128.0 / 255.0).abs() < 0.001);
+}
+
+#[test]
+fn bad_objects_are_errors() {
+    assert!(MapDefinition::from_json(&map(r#"{ "type": "dragon", "x": 0, "y": 0 }"#)).is_err());
+    assert!(MapDefinition::from_json(&map(r#"{ "type": "obstacle", "x": 0, "y": 0 }"#)).is_err());
+}
+
+#[test]
+fn map_files_load() {
+    for path in ["assets/maps/forest.json", "assets/maps/library.json"] {
+        let source = std::fs::read_to_string(path).unwrap();
+        let map = MapDefinition::from_json(&source).unwrap_or_else(|error| panic!("{}: {}", path, error));
+        assert!(!map.objects.is_empty(), "{} is empty", path);
+    }
+}
@@ -0,0 +1,75 @@
+// Collision tests for obstacles and map bounds
+
+use bevy::prelude::*;
+use rust_survivors::camera;
+use rust_survivors::map::MapDefinition;
+use rust_survivors::obstacle::{push_out, resolve_collisions, MapBounds};
+use rust_survivors::player::Player;
+
+const SIZE: Vec2 = Vec2::new(10.0, 10.0);
+
+#[test]
+fn boxes_outside_are_not_moved() {
+    let wall = Rect::new(0.0, 0.0, 100.0, 100.0);
+    assert_eq!(push_out(Vec2::new(-20.0, 50.0), SIZE, wall), Vec2::new(-20.0, 50.0));
+    // Touching the edge doesn't count as overlapping
+    assert_eq!(push_out(Vec2::new(-5.0, 50.0), SIZE, wall), Vec2::new(-5.0, 50.0));
+}
+
+#[test]
+fn boxes_are_pushed_out_the_shortest_way() {
+    let wall = Rect::new(0.0, 0.0, 100.0, 100.0);
+    assert_eq!(push_out(Vec2::new(2.0, 50.0), SIZE, wall), Vec2::new(-5.0, 50.0));
+    assert_eq!(push_out(Vec2::new(97.0, 50.0), SIZE, wall), Vec2::new(105.0, 50.0));
+    assert_eq!(push_out(Vec2::new(50.0, 1.0), SIZE, wall), Vec2::new(50.0, -5.0));
+    assert_eq!(push_out(Vec2::new(50.0, 99.0), SIZE, wall), Vec2::new(50.0, 105.0));
+}
+
+#[test]
+fn endless_maps_have_no_bounds() {
+    let bounds = MapBounds::default();
+    assert_eq!(bounds.clamp(Vec2::new(1e6, -1e6), SIZE), Vec2::new(1e6, -1e6));
+}
+
+#[test]
+fn bounds_keep_boxes_inside() {
+    let bounds = MapBounds(Some(Rect::new(-100.0, -50.0, 100.0, 50.0)));
+    assert_eq!(bounds.clamp(Vec2::new(500.0, 0.0), SIZE), Vec2::new(95.0, 0.0));
+    assert_eq!(bounds.clamp(Vec2::new(0.0, -500.0), SIZE), Vec2::new(0.0, -45.0));
+    // A box bigger than the bounds is centered, this is how the camera handles small arenas
+    assert_eq!(bounds.clamp(Vec2::new(30.0, 20.0), Vec2::new(400.0, 10.0)), Vec2::new(0.0, 20.0));
+}
+
+#[test]
+fn collisions_are_resolved_against_every_obstacle() {
+    let obstacles = [Rect::new(0.0, 0.0, 10.0, 100.0), Rect::new(-100.0, -100.0, 100.0, -10.0)];
+    let bounds = MapBounds(Some(Rect::new(-200.0, -200.0, 200.0, 200.0)));
+    let position = resolve_collisions(Vec2::new(-2.0, -12.0), SIZE, &obstacles, &bounds);
+    assert_eq!(position, Vec2::new(-2.0, -5.0));
+}
+
+// The library is a long bounded hallway, the camera stops at its walls however far the player gets
+#[test]
+fn library_keeps_the_camera_inside() {
+    let source = std::fs::read_to_string("assets/maps/library.json").unwrap();
+    let map = MapDefinition::from_json(&source).unwrap();
+    assert!(map.bounded);
+    let bounds = map.bounds().0.unwrap();
+
+    let mut app = App::new();
+    app.insert_resource(map.bounds()).add_systems(Update, camera::follow_player);
+    let player = app.world.spawn((Transform::default(), Player::default())).id();
+    let projection = OrthographicProjection {
+        area: Rect::from_center_size(Vec2::ZERO, Vec2::new(1280.0, 720.0)),
+        ..default()
+    };
+    let camera = app.world.spawn((Transform::default(), projection, camera::Camera)).id();
+    for position in [Vec2::new(100_000.0, 0.0), Vec2::new(-100_000.0, 5_000.0), Vec2::new(0.0, -5_000.0)] {
+        app.world.get_mut::<Transform>(player).unwrap().translation = position.extend(0.0);
+        app.update();
+        let center = app.world.get::<Transform>(camera).unwrap().translation.truncate();
+        let view = Rect::from_center_size(center, Vec2::new(1280.0, 720.0));
+        assert!(bounds.contains(view.min) && bounds.contains(view.max), "{:?} sees outside {:?}", view, bounds);
+    }
+}
+
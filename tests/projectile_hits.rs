@@ -0,0 +1,97 @@
+// A projectile hits one thing, even when an enemy stands right in front of a prop
+// Weapons with a crit chance land critical hits about that often
+
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rust_survivors::damage::DamageEvent;
+use rust_survivors::enemy::{self, EnemyKind};
+use rust_survivors::projectile::{Projectile, ProjectileMotion};
+use rust_survivors::prop::{self, Destructible, Prop, PropKind};
+use rust_survivors::status::StatusEvent;
+use rust_survivors::weapon;
+
+#[derive(Resource, Default)]
+struct Targets(Vec<Entity>);
+
+fn record_targets(mut damage_events: EventReader<DamageEvent>, mut targets: ResMut<Targets>) {
+    targets.0.extend(damage_events.read().map(|event| event.target));
+}
+
+fn app() -> App {
+    let mut app = App::new();
+    app.init_resource::<Targets>()
+        .add_event::<DamageEvent>()
+        .add_event::<StatusEvent>()
+        .add_systems(
+            Update,
+            (enemy::enemy_collision, prop::prop_collision, record_targets).chain(),
+        );
+    app
+}
+
+fn spawn_projectile(app: &mut App) -> Entity {
+    app.world
+        .spawn((
+            Transform::default(),
+            Projectile::new(Vec2::ZERO, Vec2::X, ProjectileMotion::Straight, 1.0),
+        ))
+        .id()
+}
+
+fn spawn_crate(app: &mut App) -> Entity {
+    app.world
+        .spawn((
+            Transform::default(),
+            Prop {
+                kind: PropKind::Crate,
+                size: PropKind::Crate.size(),
+            },
+            Destructible { health: 3 },
+        ))
+        .id()
+}
+
+#[test]
+fn projectile_on_an_enemy_and_a_prop_only_hits_the_enemy() {
+    let mut app = app();
+    let enemy = app.world.spawn((Transform::default(), EnemyKind::Skeleton.stats())).id();
+    spawn_crate(&mut app);
+    let projectile = spawn_projectile(&mut app);
+    app.update();
+    assert_eq!(app.world.resource::<Targets>().0, [enemy]);
+    assert!(app.world.get_entity(projectile).is_none());
+}
+
+#[test]
+fn props_are_hit_by_their_own_size() {
+    let mut app = app();
+    let prop = spawn_crate(&mut app);
+    // Just inside the edge of the crate's hitbox, there is no sprite to read the size from
+    app.world.get_mut::<Transform>(prop).unwrap().translation.x = PropKind::Crate.size().x / 2.0 + 4.0;
+    spawn_projectile(&mut app);
+    app.update();
+    assert_eq!(app.world.resource::<Targets>().0, [prop]);
+}
+
+// How often projectiles of the weapon land a critical hit, and the damage they did
+fn crits(weapon: weapon::Weapon) -> (f32, Vec<i32>) {
+    let mut projectile = Projectile::new(Vec2::ZERO, Vec2::X, ProjectileMotion::Straight, 1.0);
+    projectile.crit_chance = weapon.crit_chance;
+    let mut rng = StdRng::seed_from_u64(3);
+    let hits: Vec<(i32, bool)> = (0..10_000).map(|_| projectile.roll_damage(&mut rng)).collect();
+    let critical = hits.iter().filter(|(_, critical)| *critical).count();
+    let mut amounts: Vec<i32> = hits.iter().map(|(amount, _)| *amount).collect();
+    amounts.sort();
+    amounts.dedup();
+    (critical as f32 / hits.len() as f32, amounts)
+}
+
+#[test]
+fn knives_land_critical_hits_for_double_damage() {
+    let (rate, amounts) = crits(weapon::knife());
+    assert!((rate - 0.2).abs() < 0.02);
+    assert_eq!(amounts, [1, 2]);
+    // Weapons without a crit chance never crit
+    assert_eq!(crits(weapon::magic_wand()), (0.0, vec![1]));
+}
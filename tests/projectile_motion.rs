@@ -0,0 +1,126 @@
+// Trajectory tests for every projectile motion, stepped with a fixed time step
+
+use bevy::prelude::*;
+use rust_survivors::projectile::{Projectile, ProjectileMotion};
+
+const STEP: f32 = 1.0 / 60.0;
+
+// Run the projectile from position for the given amount of steps and return where it ends up
+fn simulate(
+    projectile: &mut Projectile,
+    mut position: Vec2,
+    steps: usize,
+    player: Vec2,
+    target: Option<Vec2>,
+) -> Vec2 {
+    for _ in 0..steps {
+        position = projectile.advance(position, STEP, player, target);
+    }
+    position
+}
+
+#[test]
+fn straight_moves_at_constant_speed() {
+    let mut projectile = Projectile::new(Vec2::ZERO, Vec2::new(100.0, 0.0), ProjectileMotion::Straight, 5.0);
+    let position = simulate(&mut projectile, Vec2::ZERO, 60, Vec2::ZERO, None);
+    assert!((position - Vec2::new(100.0, 0.0)).length() < 0.01);
+    assert_eq!(projectile.velocity(), Vec2::new(100.0, 0.0));
+}
+
+#[test]
+fn boomerang_stops_then_returns_to_player() {
+    let motion = ProjectileMotion::Boomerang { deceleration: 300.0 };
+    let mut projectile = Projectile::new(Vec2::ZERO, Vec2::new(300.0, 0.0), motion, 10.0);
+
+    // After one second it has used up all of its speed, the furthest point is v^2 / 2a = 150
+    let furthest = simulate(&mut projectile, Vec2::ZERO, 60, Vec2::ZERO, None);
+    assert!(projectile.returning);
+    assert!((furthest.x - 150.0).abs() < 5.0);
+
+    // It then accelerates back and is finished once it reaches the player
+    let mut position = furthest;
+    let mut steps = 0;
+    while !projectile.is_finished(position, Vec2::ZERO) {
+        position = projectile.advance(position, STEP, Vec2::ZERO, None);
+        steps += 1;
+        assert!(steps < 120, "boomerang never came back");
+    }
+    assert!(position.length() < 20.0);
+}
+
+#[test]
+fn homing_turns_towards_target() {
+    let motion = ProjectileMotion::Homing { turn_rate: 3.0 };
+    let mut projectile = Projectile::new(Vec2::ZERO, Vec2::new(100.0, 0.0), motion, 10.0);
+    let target = Vec2::new(0.0, 200.0);
+
+    // The turn is limited, after a single step the direction barely changed
+    let position = simulate(&mut projectile, Vec2::ZERO, 1, Vec2::ZERO, Some(target));
+    let turned = projectile.velocity().angle_between(Vec2::new(100.0, 0.0)).abs();
+    assert!((turned - 3.0 * STEP).abs() < 0.001);
+
+    // Given enough time it flies straight at the target, keeping its speed
+    let position = simulate(&mut projectile, position, 60, Vec2::ZERO, Some(target));
+    let to_target = target - position;
+    assert!(projectile.velocity().angle_between(to_target).abs() < 0.05);
+    assert!((projectile.velocity().length() - 100.0).abs() < 0.01);
+}
+
+#[test]
+fn sine_wave_oscillates_around_firing_line() {
+    let motion = ProjectileMotion::SineWave { amplitude: 30.0, frequency: 1.0 };
+    let mut projectile = Projectile::new(Vec2::ZERO, Vec2::new(100.0, 0.0), motion, 10.0);
+
+    // A quarter period in it is at the top of the wave
+    let position = simulate(&mut projectile, Vec2::ZERO, 15, Vec2::ZERO, None);
+    assert!((position - Vec2::new(25.0, 30.0)).length() < 0.01);
+
+    // A half period in it is back on the line
+    let position = simulate(&mut projectile, position, 15, Vec2::ZERO, None);
+    assert!((position - Vec2::new(50.0, 0.0)).length() < 0.01);
+
+    // Three quarters in it is at the bottom of the wave
+    let position = simulate(&mut projectile, position, 15, Vec2::ZERO, None);
+    assert!((position - Vec2::new(75.0, -30.0)).length() < 0.01);
+}
+
+#[test]
+fn spiral_moves_outwards_while_turning() {
+    let motion = ProjectileMotion::Spiral { angular_speed: std::f32::consts::PI };
+    let origin = Vec2::new(10.0, 10.0);
+    let mut projectile = Projectile::new(origin, Vec2::new(50.0, 0.0), motion, 10.0);
+
+    // After one second it turned half a circle and is 50 away from the origin
+    let position = simulate(&mut projectile, origin, 60, Vec2::ZERO, None);
+    assert!((position - (origin + Vec2::new(-50.0, 0.0))).length() < 0.01);
+
+    // After two seconds it is back on the starting side, twice as far out
+    let position = simulate(&mut projectile, position, 60, Vec2::ZERO, None);
+    assert!((position - (origin + Vec2::new(100.0, 0.0))).length() < 0.01);
+}
+
+#[test]
+fn gravity_follows_an_arc() {
+    let motion = ProjectileMotion::Gravity { gravity: 400.0 };
+    let mut projectile = Projectile::new(Vec2::ZERO, Vec2::new(50.0, 200.0), motion, 10.0);
+
+    // Half a second in the upwards speed is used up and the projectile is at the top of the arc
+    let top = simulate(&mut projectile, Vec2::ZERO, 30, Vec2::ZERO, None);
+    assert!(projectile.y_speed.abs() < 0.01);
+    assert!((top.x - 25.0).abs() < 0.01);
+    assert!((top.y - 50.0).abs() < 2.0);
+
+    // Another half second and it has fallen back to where it started
+    let landed = simulate(&mut projectile, top, 30, Vec2::ZERO, None);
+    assert!((landed.x - 50.0).abs() < 0.01);
+    assert!(landed.y.abs() < 4.0);
+}
+
+#[test]
+fn projectiles_finish_after_their_lifetime() {
+    let mut projectile = Projectile::new(Vec2::ZERO, Vec2::new(100.0, 0.0), ProjectileMotion::Straight, 1.0);
+    let position = simulate(&mut projectile, Vec2::ZERO, 59, Vec2::ZERO, None);
+    assert!(!projectile.is_finished(position, Vec2::ZERO));
+    let position = simulate(&mut projectile, position, 2, Vec2::ZERO, None);
+    assert!(projectile.is_finished(position, Vec2::ZERO));
+}
@@ -0,0 +1,156 @@
+// Spawn point tests, enemies have to appear just outside of whatever the camera sees, on every side of it
+// Patterns keep their shape in spawn zones, and bounded maps don't pull spawns into view
+
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rust_survivors::map::SpawnZone;
+use rust_survivors::obstacle::MapBounds;
+use rust_survivors::spawning::{
+    cluster_points, place_points, point_on_border, ring_points, wall_points, SpawnPattern, SPAWN_MARGIN,
+};
+
+// A wide screen with the camera away from the origin, nothing like the old 800x600 corners
+fn visible() -> Rect {
+    Rect::from_center_size(Vec2::new(1500.0, -700.0), Vec2::new(1920.0, 1080.0))
+}
+
+fn outside(point: Vec2) -> bool {
+    !visible().contains(point)
+}
+
+// Which side of the visible area a point is on: 0 top, 1 bottom, 2 left, 3 right
+fn side(point: Vec2) -> usize {
+    let area = visible();
+    if point.y > area.max.y {
+        0
+    } else if point.y < area.min.y {
+        1
+    } else if point.x < area.min.x {
+        2
+    } else {
+        3
+    }
+}
+
+#[test]
+fn border_points_are_on_the_margin_all_around() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut sides = [0; 4];
+    for _ in 0..1000 {
+        let point = point_on_border(visible(), SPAWN_MARGIN, &mut rng);
+        assert!(outside(point), "{} is visible", point);
+        // Exactly on the edge of the visible area grown by the margin
+        assert!(visible().inset(SPAWN_MARGIN + 0.01).contains(point));
+        assert!(!visible().inset(SPAWN_MARGIN - 0.01).contains(point));
+        sides[side(point)] += 1;
+    }
+    assert!(sides.iter().all(|&count| count > 0), "{:?}", sides);
+    // The top and bottom are longer than the left and right, so they get more spawns
+    assert!(sides[0] > sides[2] && sides[1] > sides[3]);
+}
+
+#[test]
+fn ring_surrounds_the_screen() {
+    let mut rng = StdRng::seed_from_u64(2);
+    let points = ring_points(visible(), SPAWN_MARGIN, 12, &mut rng);
+    assert_eq!(points.len(), 12);
+    let radius = visible().half_size().length() + SPAWN_MARGIN;
+    let mut sides = [0; 4];
+    for point in points {
+        assert!(outside(point));
+        assert!((point.distance(visible().center()) - radius).abs() < 0.01);
+        sides[side(point)] += 1;
+    }
+    assert!(sides.iter().all(|&count| count > 0), "{:?}", sides);
+}
+
+#[test]
+fn walls_come_in_from_every_side() {
+    let mut sides = [0; 4];
+    for seed in 0..50 {
+        let points = wall_points(visible(), SPAWN_MARGIN, 8, &mut StdRng::seed_from_u64(seed));
+        assert_eq!(points.len(), 8);
+        let wall_side = side(points[0]);
+        for point in &points {
+            assert!(outside(*point));
+            assert!(visible().inset(SPAWN_MARGIN + 0.01).contains(*point));
+            assert!(!visible().inset(SPAWN_MARGIN - 0.01).contains(*point));
+            // A wall stays on one side
+            assert_eq!(side(*point), wall_side);
+        }
+        sides[wall_side] += 1;
+    }
+    assert!(sides.iter().all(|&count| count > 0), "{:?}", sides);
+}
+
+#[test]
+fn clusters_start_fully_off_screen() {
+    let mut sides = [0; 4];
+    for seed in 0..200 {
+        let points = cluster_points(visible(), SPAWN_MARGIN, 5, &mut StdRng::seed_from_u64(seed));
+        assert_eq!(points.len(), 5);
+        for point in &points {
+            assert!(outside(*point), "{} is visible", point);
+            // The group is pushed out by its spread, but not further than twice that
+            assert!(visible().inset(SPAWN_MARGIN + 80.0).contains(*point));
+        }
+        sides[side(points[0])] += 1;
+    }
+    assert!(sides.iter().all(|&count| count > 0), "{:?}", sides);
+}
+
+const ENEMY_SIZE: Vec2 = Vec2::new(50.0, 50.0);
+
+#[test]
+fn patterns_keep_their_shape_in_a_spawn_zone() {
+    let mut rng = StdRng::seed_from_u64(6);
+    let zone = SpawnZone {
+        area: Rect::from_center_size(Vec2::new(5000.0, 0.0), Vec2::splat(1000.0)),
+    };
+    for _ in 0..100 {
+        let points = SpawnPattern::Cluster { count: 5 }.points(visible(), &mut rng);
+        let placed = place_points(points.clone(), Some(&zone), visible(), &MapBounds(None), ENEMY_SIZE, &mut rng);
+        assert_eq!(placed.len(), points.len());
+        for point in &placed {
+            assert!(zone.area.contains(*point), "{} is outside of the zone", point);
+        }
+        // Away from the zone edges every enemy keeps its place in the group
+        let offset = placed[0] - points[0];
+        if zone.area.inset(-100.0).contains(placed[0]) {
+            for (placed, point) in placed.iter().zip(&points) {
+                assert!((*placed - *point - offset).length() < 0.01);
+            }
+        }
+    }
+    // A ring is much bigger than the zone, it is squeezed into it instead of being replaced by random points
+    let ring = SpawnPattern::Ring { count: 12 }.points(visible(), &mut rng);
+    let placed = place_points(ring, Some(&zone), visible(), &MapBounds(None), ENEMY_SIZE, &mut rng);
+    assert_eq!(placed.len(), 12);
+    assert!(placed.iter().all(|point| zone.area.contains(*point)));
+}
+
+#[test]
+fn bounded_maps_dont_pull_spawns_into_view() {
+    let mut rng = StdRng::seed_from_u64(7);
+    // The map is as tall as the screen, with room for enemies only to the left and right
+    let map = Rect::from_center_size(visible().center(), visible().size() + Vec2::new(600.0, 0.0));
+    let bounds = MapBounds(Some(map));
+    for _ in 0..100 {
+        let points = SpawnPattern::Ring { count: 12 }.points(visible(), &mut rng);
+        for point in place_points(points, None, visible(), &bounds, ENEMY_SIZE, &mut rng) {
+            assert!(outside(point), "{} is visible", point);
+            assert!(map.contains(point), "{} is outside of the map", point);
+        }
+    }
+}
+
+#[test]
+fn a_fully_visible_map_still_spawns_inside_of_it() {
+    let mut rng = StdRng::seed_from_u64(8);
+    let map = Rect::from_center_size(visible().center(), Vec2::splat(500.0));
+    let points = SpawnPattern::Wall { count: 8 }.points(visible(), &mut rng);
+    let placed = place_points(points, None, visible(), &MapBounds(Some(map)), ENEMY_SIZE, &mut rng);
+    assert_eq!(placed.len(), 8);
+    assert!(placed.iter().all(|point| map.contains(*point)));
+}
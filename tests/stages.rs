@@ -0,0 +1,107 @@
+// Tests for the stage registry, the wave timeline and unlocking stages from saved progress
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rust_survivors::data;
+use rust_survivors::enemy::EnemyKind;
+use rust_survivors::map::MapDefinition;
+use rust_survivors::progress::{Progress, StageRecord};
+use rust_survivors::run::format_time;
+use rust_survivors::stage::{StageRegistry, Unlock, STAGES_PATH};
+
+fn stages() -> StageRegistry {
+    let source = std::fs::read_to_string(STAGES_PATH).unwrap();
+    StageRegistry::from_ron(&source).unwrap()
+}
+
+#[test]
+fn stage_file_is_playable() {
+    let stages = stages();
+    assert_eq!(stages.0[0].unlock, Unlock::Always, "the first stage has to be unlocked");
+    for stage in &stages.0 {
+        let source = std::fs::read_to_string(&stage.map).unwrap();
+        MapDefinition::from_json(&source).unwrap_or_else(|error| panic!("{}: {}", stage.name, error));
+        assert!(!stage.enemies.is_empty(), "{} has no enemies", stage.name);
+        assert_eq!(stage.waves[0].start, 0.0, "{} has no wave at the start", stage.name);
+        assert!(stage.waves.windows(2).all(|waves| waves[0].start < waves[1].start));
+    }
+}
+
+#[test]
+fn waves_follow_the_timeline() {
+    let stage = &stages().0[0];
+    assert_eq!(stage.wave(0.0), Some(&stage.waves[0]));
+    assert_eq!(stage.wave(stage.waves[1].start - 0.1), Some(&stage.waves[0]));
+    assert_eq!(stage.wave(stage.waves[1].start), Some(&stage.waves[1]));
+    assert_eq!(stage.wave(1e9), stage.waves.last());
+}
+
+#[test]
+fn enemies_come_from_the_stage_pool() {
+    let mut stage = stages().0[0].clone();
+    stage.enemies = vec![(EnemyKind::Ghost, 3.0), (EnemyKind::Brute, 1.0)];
+    let mut rng = StdRng::seed_from_u64(3);
+    let ghosts = (0..10_000)
+        .map(|_| stage.random_enemy(&mut rng))
+        .inspect(|kind| assert!(matches!(kind, EnemyKind::Ghost | EnemyKind::Brute)))
+        .filter(|kind| *kind == EnemyKind::Ghost)
+        .count();
+    assert!((ghosts as f32 / 10_000.0 - 0.75).abs() < 0.02);
+}
+
+#[test]
+fn unlocks_check_the_saved_records() {
+    let mut progress = Progress::default();
+    let survive = Unlock::Survive { stage: "Forest".to_string(), minutes: 5 };
+    let level = Unlock::Level { stage: "Forest".to_string(), level: 10 };
+    let clear = Unlock::Clear { stage: "Forest".to_string() };
+    assert!(!survive.is_met(&progress) && !level.is_met(&progress) && !clear.is_met(&progress));
+    progress.stages.insert(
+        "Forest".to_string(),
+        StageRecord { best_level: 10, best_minutes: 5, cleared: false },
+    );
+    assert!(survive.is_met(&progress) && level.is_met(&progress) && !clear.is_met(&progress));
+    // Records on other stages don't count
+    assert!(!Unlock::Survive { stage: "Library".to_string(), minutes: 1 }.is_met(&progress));
+    progress.total_kills = 99;
+    assert!(!Unlock::Kills(100).is_met(&progress));
+    progress.total_kills = 100;
+    assert!(Unlock::Kills(100).is_met(&progress));
+}
+
+#[test]
+fn progress_survives_a_save() {
+    let mut progress = Progress {
+        total_kills: 1234,
+        ..Default::default()
+    };
+    progress.stages.insert(
+        "Forest".to_string(),
+        StageRecord { best_level: 12, best_minutes: 7, cleared: true },
+    );
+    assert_eq!(Progress::from_ron(&progress.to_ron()).unwrap(), progress);
+}
+
+#[test]
+fn run_timer_shows_minutes_and_seconds() {
+    assert_eq!(format_time(0.0), "00:00");
+    assert_eq!(format_time(59.9), "00:59");
+    assert_eq!(format_time(30.0 * 60.0), "30:00");
+    assert_eq!(stages().0[0].run_length, 30.0 * 60.0, "runs last 30 minutes unless the stage says otherwise");
+}
+
+#[test]
+fn data_files_are_found_from_any_directory() {
+    // The path doesn't depend on the working directory, it starts from where the assets folder is
+    let path = data::path(STAGES_PATH);
+    assert!(path.is_absolute());
+    assert!(path.exists());
+}
+
+#[test]
+fn missing_data_files_are_reported() {
+    let error = data::read("assets/data/missing.ron", StageRegistry::from_ron).err().unwrap();
+    assert!(error.starts_with("Could not read assets/data/missing.ron"), "{}", error);
+    let error = data::read(STAGES_PATH, |_| "broken".parse::<u32>()).err().unwrap();
+    assert!(error.starts_with("Could not parse assets/data/stages.ron"), "{}", error);
+}
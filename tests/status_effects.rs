@@ -0,0 +1,145 @@
+// Stacking rules of the status effects, and the statuses every kind of enemy ignores
+
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use rust_survivors::damage::{DamageEvent, DamageKind};
+use rust_survivors::enemy::EnemyKind;
+use rust_survivors::status::{
+    self, StatusEffect, StatusEffects, StatusEvent, StatusKind, DAMAGE_TICK_RATE, MAX_POISON_STACKS,
+};
+use std::time::Duration;
+
+const STEP: f32 = 0.25;
+
+fn effect(kind: StatusKind, duration: f32, potency: f32) -> StatusEffect {
+    StatusEffect { kind, duration, potency }
+}
+
+fn remaining(statuses: &StatusEffects, kind: StatusKind) -> Vec<f32> {
+    statuses
+        .active
+        .iter()
+        .filter(|status| status.kind == kind)
+        .map(|status| status.timer.remaining_secs())
+        .collect()
+}
+
+#[test]
+fn poison_stacks_up_to_the_max() {
+    let mut statuses = StatusEffects::default();
+    for _ in 0..MAX_POISON_STACKS + 3 {
+        statuses.apply(effect(StatusKind::Poison, 4.0, 1.0));
+    }
+    assert_eq!(statuses.stacks(StatusKind::Poison), MAX_POISON_STACKS);
+    assert_eq!(statuses.damage_per_tick(), MAX_POISON_STACKS as f32);
+}
+
+#[test]
+fn poison_at_max_stacks_refreshes_the_stack_closest_to_running_out() {
+    let mut statuses = StatusEffects::default();
+    for index in 0..MAX_POISON_STACKS {
+        statuses.apply(effect(StatusKind::Poison, 1.0 + index as f32, 1.0));
+    }
+    statuses.apply(effect(StatusKind::Poison, 10.0, 2.0));
+    let mut timers = remaining(&statuses, StatusKind::Poison);
+    timers.sort_by(f32::total_cmp);
+    assert_eq!(timers, [2.0, 3.0, 4.0, 5.0, 10.0]);
+    // The refreshed stack keeps the stronger potency
+    assert_eq!(statuses.poison_damage(), 6.0);
+}
+
+#[test]
+fn burn_refreshes_instead_of_stacking() {
+    let mut statuses = StatusEffects::default();
+    statuses.apply(effect(StatusKind::Burn, 3.0, 2.0));
+    statuses.apply(effect(StatusKind::Burn, 1.0, 1.0));
+    assert_eq!(statuses.stacks(StatusKind::Burn), 1);
+    // A shorter, weaker burn neither shortens nor weakens the one that is there
+    assert_eq!(remaining(&statuses, StatusKind::Burn), [3.0]);
+    assert_eq!(statuses.burn_damage(), 2.0);
+    statuses.apply(effect(StatusKind::Burn, 5.0, 3.0));
+    assert_eq!(remaining(&statuses, StatusKind::Burn), [5.0]);
+    assert_eq!(statuses.burn_damage(), 3.0);
+}
+
+#[test]
+fn the_strongest_slow_wins() {
+    let mut statuses = StatusEffects::default();
+    statuses.apply(effect(StatusKind::Slow, 2.0, 0.5));
+    statuses.apply(effect(StatusKind::Slow, 2.0, 0.8));
+    assert_eq!(statuses.movement_multiplier(), 0.5);
+    statuses.apply(effect(StatusKind::Slow, 2.0, 0.3));
+    assert_eq!(statuses.movement_multiplier(), 0.3);
+    statuses.apply(effect(StatusKind::Freeze, 1.0, 0.0));
+    assert_eq!(statuses.movement_multiplier(), 0.0);
+}
+
+#[test]
+fn weaken_multiplies_damage_taken() {
+    let mut statuses = StatusEffects::default();
+    assert_eq!(statuses.damage_taken_multiplier(), 1.0);
+    statuses.apply(effect(StatusKind::Weaken, 2.0, 1.5));
+    statuses.apply(effect(StatusKind::Weaken, 2.0, 1.25));
+    assert_eq!(statuses.damage_taken_multiplier(), 1.5);
+}
+
+// The damage of one status tick, by kind
+fn tick_damage(effects: &[StatusEffect]) -> Vec<(DamageKind, i32)> {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(STEP)))
+        .add_event::<DamageEvent>()
+        .add_systems(Update, status::tick_status_effects);
+    let mut statuses = StatusEffects::default();
+    for effect in effects {
+        statuses.apply(*effect);
+    }
+    app.world.spawn(statuses);
+    // The first update only starts the clock, the tick lands once DAMAGE_TICK_RATE has passed after that
+    app.update();
+    for _ in 0..(DAMAGE_TICK_RATE / STEP).round() as usize {
+        app.update();
+    }
+    app.world
+        .resource_mut::<Events<DamageEvent>>()
+        .drain()
+        .map(|event| (event.kind, event.amount))
+        .collect()
+}
+
+#[test]
+fn burn_and_poison_ticks_add_up_to_their_rounded_total() {
+    let weak_burn = effect(StatusKind::Burn, 4.0, 0.3);
+    let weak_poison = effect(StatusKind::Poison, 4.0, 0.3);
+    // Together they round to 1 damage, splitting them by kind doesn't turn that into 2
+    assert_eq!(tick_damage(&[weak_burn, weak_poison]), vec![(DamageKind::Poison, 1)]);
+    assert_eq!(tick_damage(&[weak_burn]), vec![(DamageKind::Fire, 1)]);
+    assert_eq!(
+        tick_damage(&[effect(StatusKind::Burn, 4.0, 2.0), effect(StatusKind::Poison, 4.0, 1.4)]),
+        vec![(DamageKind::Fire, 2), (DamageKind::Poison, 1)]
+    );
+}
+
+// Send every kind of status to an enemy of the given kind and return the statuses it ended up with
+fn statuses_taken(kind: EnemyKind) -> Vec<StatusKind> {
+    let mut app = App::new();
+    app.add_event::<StatusEvent>().add_systems(Update, status::apply_status);
+    let enemy = app.world.spawn((kind.stats(), StatusEffects::default())).id();
+    for status in [StatusKind::Freeze, StatusKind::Slow, StatusKind::Burn, StatusKind::Poison, StatusKind::Weaken] {
+        app.world.send_event(StatusEvent {
+            target: enemy,
+            effect: effect(status, 1.0, 1.0),
+        });
+    }
+    app.update();
+    let statuses = app.world.get::<StatusEffects>(enemy).unwrap();
+    statuses.active.iter().map(|status| status.kind).collect()
+}
+
+#[test]
+fn enemies_ignore_the_statuses_they_are_immune_to() {
+    assert_eq!(statuses_taken(EnemyKind::Bat).len(), 5);
+    assert!(!statuses_taken(EnemyKind::Skeleton).contains(&StatusKind::Poison));
+    assert!(!statuses_taken(EnemyKind::Brute).contains(&StatusKind::Slow));
+    assert!(statuses_taken(EnemyKind::Brute).contains(&StatusKind::Poison));
+}
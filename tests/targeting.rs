@@ -0,0 +1,79 @@
+// Target picking for every targeting mode, and how volleys fan out around the aim direction
+
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rust_survivors::weapon::{pick_target, spread_angle, Targeting};
+
+const PLAYER: Vec2 = Vec2::new(10.0, 10.0);
+const FACING: Vec2 = Vec2::new(0.0, 1.0);
+
+// (position, health), the closest enemy is neither the strongest nor the weakest
+fn enemies() -> Vec<(Vec2, i32)> {
+    vec![
+        (Vec2::new(100.0, 10.0), 5),
+        (Vec2::new(20.0, 10.0), 3),
+        (Vec2::new(-200.0, 10.0), 9),
+        (Vec2::new(10.0, -300.0), 1),
+    ]
+}
+
+fn pick(targeting: Targeting, cursor: Option<Vec2>, enemies: &[(Vec2, i32)]) -> Option<Vec2> {
+    pick_target(targeting, PLAYER, FACING, cursor, enemies, &mut StdRng::seed_from_u64(3))
+}
+
+#[test]
+fn nearest_picks_the_closest_enemy() {
+    assert_eq!(pick(Targeting::Nearest, None, &enemies()), Some(Vec2::new(20.0, 10.0)));
+}
+
+#[test]
+fn strongest_and_lowest_health_pick_by_health() {
+    assert_eq!(pick(Targeting::Strongest, None, &enemies()), Some(Vec2::new(-200.0, 10.0)));
+    assert_eq!(pick(Targeting::LowestHealth, None, &enemies()), Some(Vec2::new(10.0, -300.0)));
+}
+
+#[test]
+fn random_picks_every_enemy_eventually() {
+    let enemies = enemies();
+    let mut rng = StdRng::seed_from_u64(11);
+    let mut picked = [false; 4];
+    for _ in 0..200 {
+        let target = pick_target(Targeting::Random, PLAYER, FACING, None, &enemies, &mut rng).unwrap();
+        let index = enemies.iter().position(|enemy| enemy.0 == target).unwrap();
+        picked[index] = true;
+    }
+    assert_eq!(picked, [true; 4]);
+}
+
+#[test]
+fn facing_and_cursor_dont_need_enemies() {
+    assert_eq!(pick(Targeting::Facing, None, &[]), Some(PLAYER + FACING));
+    assert_eq!(pick(Targeting::MouseCursor, Some(Vec2::new(5.0, 6.0)), &[]), Some(Vec2::new(5.0, 6.0)));
+    assert_eq!(pick(Targeting::MouseCursor, None, &enemies()), None);
+}
+
+#[test]
+fn enemy_targeting_without_enemies_finds_nothing() {
+    for targeting in [Targeting::Nearest, Targeting::Random, Targeting::Strongest, Targeting::LowestHealth] {
+        assert_eq!(pick(targeting, None, &[]), None);
+    }
+}
+
+#[test]
+fn single_projectile_flies_straight() {
+    assert_eq!(spread_angle(0, 1, 30.0), 0.0);
+}
+
+#[test]
+fn volleys_fan_out_evenly_around_the_aim() {
+    let angles: Vec<f32> = (0..3).map(|index| spread_angle(index, 3, 15.0).to_degrees()).collect();
+    for (angle, expected) in angles.iter().zip([-15.0, 0.0, 15.0]) {
+        assert!((angle - expected).abs() < 1e-4, "{:?}", angles);
+    }
+    // An even volley leaves the middle free, half a spread on either side
+    let angles: Vec<f32> = (0..4).map(|index| spread_angle(index, 4, 10.0).to_degrees()).collect();
+    for (angle, expected) in angles.iter().zip([-15.0, -5.0, 5.0, 15.0]) {
+        assert!((angle - expected).abs() < 1e-4, "{:?}", angles);
+    }
+}